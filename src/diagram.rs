@@ -0,0 +1,401 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+use clap::ValueEnum;
+use mmod::{is_platform_dependency, DependencyGraph, ModMetadata, Platform};
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum DiagramFormat {
+    /// Graphviz DOT
+    Dot,
+    /// Mermaid flowchart
+    Mermaid,
+    /// Standalone SVG image
+    Svg,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum NodeStatus {
+    Ok,
+    /// A mandatory dependency that no mod provides.
+    Missing,
+    /// A mod whose version doesn't satisfy one of its dependents.
+    Conflicting,
+    /// A member of a mandatory dependency cycle.
+    InCycle,
+}
+
+struct Node {
+    id: String,
+    label: String,
+    platform: Option<Platform>,
+    status: NodeStatus,
+}
+
+struct DiagramEdge {
+    from: usize,
+    to: usize,
+    optional: bool,
+    satisfied: bool,
+}
+
+/// The mods of a graph, plus a node for each missing mandatory dependency.
+struct Diagram {
+    nodes: Vec<Node>,
+    edges: Vec<DiagramEdge>,
+}
+
+impl Diagram {
+    fn new(graph: &DependencyGraph) -> Self {
+        let mut mods: Vec<&ModMetadata> = graph.nodes().to_vec();
+        mods.sort_by(|a, b| a.mod_id.cmp(&b.mod_id));
+
+        let in_cycle: HashSet<String> = graph.cycles().into_iter().flatten().collect();
+        let mut nodes: Vec<Node> = mods.iter()
+            .map(|m| Node {
+                id: m.mod_id.clone(),
+                label: format!("{}\n{}", m.mod_id, m.version),
+                platform: Some(m.platform.clone()),
+                status: if in_cycle.contains(&m.mod_id) { NodeStatus::InCycle } else { NodeStatus::Ok },
+            })
+            .collect();
+        let mut edges = Vec::new();
+
+        for (from, mod_) in mods.iter().enumerate() {
+            for dep in mod_.dependencies.iter().filter(|dep| !is_platform_dependency(&dep.mod_id)) {
+                let (to, satisfied) = match graph.get(&dep.mod_id) {
+                    Some(target) => {
+                        let to = mods.iter().position(|m| m.mod_id == target.mod_id).unwrap();
+                        (to, dep.version_range.matches(&target.version))
+                    }
+                    None if dep.mandatory => {
+                        let to = nodes.iter().position(|n| n.id == dep.mod_id).unwrap_or_else(|| {
+                            nodes.push(Node {
+                                id: dep.mod_id.clone(),
+                                label: format!("{}\nmissing", dep.mod_id),
+                                platform: None,
+                                status: NodeStatus::Missing,
+                            });
+                            nodes.len() - 1
+                        });
+                        (to, false)
+                    }
+                    None => continue,
+                };
+
+                if !satisfied && nodes[to].status == NodeStatus::Ok {
+                    nodes[to].status = NodeStatus::Conflicting;
+                }
+                edges.push(DiagramEdge { from, to, optional: !dep.mandatory, satisfied });
+            }
+        }
+
+        Diagram { nodes, edges }
+    }
+
+    /// Node indices grouped by platform, in order of first appearance.
+    /// Missing dependencies are not part of any platform.
+    fn clusters(&self) -> Vec<(Option<&Platform>, Vec<usize>)> {
+        let mut clusters: Vec<(Option<&Platform>, Vec<usize>)> = Vec::new();
+        for (i, node) in self.nodes.iter().enumerate() {
+            match clusters.iter_mut().find(|(platform, _)| *platform == node.platform.as_ref()) {
+                Some((_, members)) => members.push(i),
+                None => clusters.push((node.platform.as_ref(), vec![i])),
+            }
+        }
+        clusters
+    }
+}
+
+/// Renders the dependency graph, clustered by platform, with problems highlighted.
+pub fn render(graph: &DependencyGraph, format: DiagramFormat) -> String {
+    let diagram = Diagram::new(graph);
+    match format {
+        DiagramFormat::Dot => render_dot(&diagram),
+        DiagramFormat::Mermaid => render_mermaid(&diagram),
+        DiagramFormat::Svg => render_svg(&diagram),
+    }
+}
+
+fn render_dot(diagram: &Diagram) -> String {
+    let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n"));
+    let mut out = String::new();
+
+    writeln!(out, "digraph mods {{").unwrap();
+    writeln!(out, "    rankdir=LR;").unwrap();
+    writeln!(out, "    node [shape=box, style=rounded];").unwrap();
+
+    for (platform, members) in diagram.clusters() {
+        let indent = match platform {
+            Some(platform) => {
+                writeln!(out, "    subgraph {} {{", quote(&format!("cluster_{}", platform_name(platform)))).unwrap();
+                writeln!(out, "        label={};", quote(&platform_name(platform))).unwrap();
+                "        "
+            }
+            None => "    ",
+        };
+        for i in members {
+            let node = &diagram.nodes[i];
+            let style = match node.status {
+                NodeStatus::Ok => "",
+                NodeStatus::Missing => ", color=red, fontcolor=red, style=\"rounded,dashed\"",
+                NodeStatus::Conflicting => ", color=red, fontcolor=red",
+                NodeStatus::InCycle => ", color=orange, fontcolor=orange",
+            };
+            writeln!(out, "{}{} [label={}{}];", indent, quote(&node.id), quote(&node.label), style).unwrap();
+        }
+        if platform.is_some() {
+            writeln!(out, "    }}").unwrap();
+        }
+    }
+
+    for edge in &diagram.edges {
+        let mut attributes = Vec::new();
+        if edge.optional {
+            attributes.push("style=dashed");
+        }
+        if !edge.satisfied {
+            attributes.push("color=red");
+        }
+        let attributes = if attributes.is_empty() { String::new() } else { format!(" [{}]", attributes.join(", ")) };
+        writeln!(
+            out,
+            "    {} -> {}{};",
+            quote(&diagram.nodes[edge.from].id),
+            quote(&diagram.nodes[edge.to].id),
+            attributes,
+        ).unwrap();
+    }
+
+    writeln!(out, "}}").unwrap();
+    out
+}
+
+fn render_mermaid(diagram: &Diagram) -> String {
+    let label = |s: &str| s.replace('"', "#quot;").replace('\n', "<br/>");
+    let mut out = String::new();
+
+    writeln!(out, "flowchart LR").unwrap();
+    for (cluster, (platform, members)) in diagram.clusters().into_iter().enumerate() {
+        let indent = match platform {
+            Some(platform) => {
+                // Subgraph ids must be plain identifiers, unlike `Unknown` loader names.
+                writeln!(out, "    subgraph platform{}[\"{}\"]", cluster, label(&platform_name(platform))).unwrap();
+                "        "
+            }
+            None => "    ",
+        };
+        for i in members {
+            writeln!(out, "{}n{}[\"{}\"]", indent, i, label(&diagram.nodes[i].label)).unwrap();
+        }
+        if platform.is_some() {
+            writeln!(out, "    end").unwrap();
+        }
+    }
+
+    let mut failed_links = Vec::new();
+    for (i, edge) in diagram.edges.iter().enumerate() {
+        let arrow = if edge.optional { "-.->" } else { "-->" };
+        writeln!(out, "    n{} {} n{}", edge.from, arrow, edge.to).unwrap();
+        if !edge.satisfied {
+            failed_links.push(i.to_string());
+        }
+    }
+
+    writeln!(out, "    classDef missing stroke:#c00,stroke-dasharray:4,color:#c00").unwrap();
+    writeln!(out, "    classDef conflicting fill:#fdd,stroke:#c00").unwrap();
+    writeln!(out, "    classDef cycle fill:#ffe0b3,stroke:#e69500").unwrap();
+    for (i, node) in diagram.nodes.iter().enumerate() {
+        let class = match node.status {
+            NodeStatus::Ok => continue,
+            NodeStatus::Missing => "missing",
+            NodeStatus::Conflicting => "conflicting",
+            NodeStatus::InCycle => "cycle",
+        };
+        writeln!(out, "    class n{} {}", i, class).unwrap();
+    }
+    if !failed_links.is_empty() {
+        writeln!(out, "    linkStyle {} stroke:#c00", failed_links.join(",")).unwrap();
+    }
+
+    out
+}
+
+pub fn platform_name(platform: &Platform) -> String {
+    match platform {
+        Platform::Unknown(name) => name.clone(),
+        platform => format!("{:?}", platform),
+    }
+}
+
+const SVG_NODE_WIDTH: usize = 200;
+const SVG_LABEL_CHARS: usize = 28;
+const SVG_NODE_HEIGHT: usize = 40;
+const SVG_COLUMN_GAP: usize = 80;
+const SVG_ROW_GAP: usize = 20;
+const SVG_MARGIN: usize = 20;
+
+/// Lays the nodes out in columns, each mod one column left of its dependencies.
+fn render_svg(diagram: &Diagram) -> String {
+    let node_count = diagram.nodes.len();
+
+    // Longest path from a top-level mod, ignoring the edges that close a cycle.
+    let back_edges = back_edges(diagram);
+    let mut columns = vec![0; node_count];
+    loop {
+        let mut changed = false;
+        for (i, edge) in diagram.edges.iter().enumerate() {
+            let column = columns[edge.from] + 1;
+            if !back_edges.contains(&i) && column > columns[edge.to] {
+                columns[edge.to] = column;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    let mut rows = vec![0; node_count];
+    let mut column_sizes: Vec<usize> = Vec::new();
+    for (i, &column) in columns.iter().enumerate() {
+        if column_sizes.len() <= column {
+            column_sizes.resize(column + 1, 0);
+        }
+        rows[i] = column_sizes[column];
+        column_sizes[column] += 1;
+    }
+
+    let x = |i: usize| SVG_MARGIN + columns[i] * (SVG_NODE_WIDTH + SVG_COLUMN_GAP);
+    let y = |i: usize| SVG_MARGIN + rows[i] * (SVG_NODE_HEIGHT + SVG_ROW_GAP);
+    let width = SVG_MARGIN * 2 + column_sizes.len().max(1) * (SVG_NODE_WIDTH + SVG_COLUMN_GAP) - SVG_COLUMN_GAP;
+    let height = SVG_MARGIN * 2 + column_sizes.iter().max().copied().unwrap_or(1) * (SVG_NODE_HEIGHT + SVG_ROW_GAP) - SVG_ROW_GAP;
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\" font-family=\"sans-serif\" font-size=\"12\">",
+        width, height,
+    ).unwrap();
+    writeln!(out, "  <defs><marker id=\"arrow\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" markerWidth=\"6\" markerHeight=\"6\" orient=\"auto\"><path d=\"M0,0 L10,5 L0,10 z\" fill=\"context-stroke\"/></marker></defs>").unwrap();
+
+    for edge in &diagram.edges {
+        let (x1, y1) = (x(edge.from) + SVG_NODE_WIDTH, y(edge.from) + SVG_NODE_HEIGHT / 2);
+        let (x2, y2) = (x(edge.to), y(edge.to) + SVG_NODE_HEIGHT / 2);
+        let color = if edge.satisfied { "#666" } else { "#c00" };
+        let dash = if edge.optional { " stroke-dasharray=\"5,4\"" } else { "" };
+        writeln!(
+            out,
+            "  <path d=\"M{},{} C{},{} {},{} {},{}\" fill=\"none\" stroke=\"{}\"{} marker-end=\"url(#arrow)\"/>",
+            x1, y1, x1 + SVG_COLUMN_GAP / 2, y1, x2.saturating_sub(SVG_COLUMN_GAP / 2), y2, x2, y2, color, dash,
+        ).unwrap();
+    }
+
+    for (i, node) in diagram.nodes.iter().enumerate() {
+        let (fill, stroke, dash) = match node.status {
+            NodeStatus::Ok => ("#eef4ff", "#4a6fa5", ""),
+            NodeStatus::Missing => ("#fff", "#c00", " stroke-dasharray=\"4,3\""),
+            NodeStatus::Conflicting => ("#fdd", "#c00", ""),
+            NodeStatus::InCycle => ("#ffe0b3", "#e69500", ""),
+        };
+        let title = match &node.platform {
+            Some(platform) => format!("{} ({})", node.id, platform_name(platform)),
+            None => format!("{} (missing)", node.id),
+        };
+        writeln!(out, "  <g><title>{}</title>", escape_xml(&title)).unwrap();
+        writeln!(
+            out,
+            "    <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"6\" fill=\"{}\" stroke=\"{}\"{}/>",
+            x(i), y(i), SVG_NODE_WIDTH, SVG_NODE_HEIGHT, fill, stroke, dash,
+        ).unwrap();
+        for (line, text) in node.label.lines().enumerate() {
+            let text = if text.chars().count() > SVG_LABEL_CHARS {
+                format!("{}…", text.chars().take(SVG_LABEL_CHARS - 1).collect::<String>())
+            } else {
+                text.to_string()
+            };
+            writeln!(
+                out,
+                "    <text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{}</text>",
+                x(i) + SVG_NODE_WIDTH / 2, y(i) + 16 + line * 14, escape_xml(&text),
+            ).unwrap();
+        }
+        writeln!(out, "  </g>").unwrap();
+    }
+
+    writeln!(out, "</svg>").unwrap();
+    out
+}
+
+/// Indices of the edges that point back to a node on the current depth-first
+/// search path. Removing them leaves the graph acyclic.
+fn back_edges(diagram: &Diagram) -> HashSet<usize> {
+    fn visit(diagram: &Diagram, node: usize, state: &mut [u8], back_edges: &mut HashSet<usize>) {
+        state[node] = 1;
+        for (i, edge) in diagram.edges.iter().enumerate().filter(|(_, edge)| edge.from == node) {
+            match state[edge.to] {
+                0 => visit(diagram, edge.to, state, back_edges),
+                1 => {
+                    back_edges.insert(i);
+                }
+                _ => {}
+            }
+        }
+        state[node] = 2;
+    }
+
+    // 0: not visited, 1: on the current path, 2: done
+    let mut state = vec![0; diagram.nodes.len()];
+    let mut back_edges = HashSet::new();
+    for node in 0..diagram.nodes.len() {
+        if state[node] == 0 {
+            visit(diagram, node, &mut state, &mut back_edges);
+        }
+    }
+    back_edges
+}
+
+pub fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_dot() {
+        let mods = [
+            ModMetadata::new("app", "1.0.0")
+                .with_dependency("lib", ">=2.0.0", true)
+                .with_dependency("extra", "*", false)
+                .with_dependency("gone", "*", true),
+            ModMetadata::new("lib", "1.0.0"),
+            ModMetadata::new("extra", "1.0.0"),
+        ];
+        let refs: Vec<_> = mods.iter().collect();
+        let dot = render(&DependencyGraph::new(&refs), DiagramFormat::Dot);
+
+        assert!(dot.contains("subgraph \"cluster_Fabric\""));
+        assert!(dot.contains("\"lib\" [label=\"lib\\n1.0.0\", color=red, fontcolor=red];"));
+        assert!(dot.contains("\"gone\" [label=\"gone\\nmissing\", color=red, fontcolor=red, style=\"rounded,dashed\"];"));
+        assert!(dot.contains("\"app\" -> \"extra\" [style=dashed];"));
+        assert!(dot.contains("\"app\" -> \"lib\" [color=red];"));
+    }
+
+    #[test]
+    fn test_render_mermaid_subgraphs() {
+        let mods = [
+            ModMetadata::new("app", "1.0.0"),
+            ModMetadata::new("plugin", "1.0.0").with_platform(Platform::Unknown("my \"loader\"".to_string())),
+        ];
+        let refs: Vec<_> = mods.iter().collect();
+        let mermaid = render(&DependencyGraph::new(&refs), DiagramFormat::Mermaid);
+
+        assert!(mermaid.contains("    subgraph platform0[\"Fabric\"]\n"), "{}", mermaid);
+        assert!(mermaid.contains("    subgraph platform1[\"my #quot;loader#quot;\"]\n"), "{}", mermaid);
+    }
+}
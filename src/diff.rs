@@ -0,0 +1,267 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+use anyhow::Result;
+use clap::ValueEnum;
+use crate::lock;
+use mmod::{compare_versions, detect_loader, scan_dirs, select_loader_entries, ModMetadata, Platform, ScanOptions};
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum DiffFormat {
+    /// Plain text
+    Text,
+    /// Markdown, for release notes
+    Markdown,
+}
+
+/// How the mods of two mod sets differ, by `mod_id`.
+#[derive(Default)]
+pub struct ModSetDiff<'a> {
+    pub added: Vec<&'a ModMetadata>,
+    pub removed: Vec<&'a ModMetadata>,
+    /// Mods whose version increased, as `(old, new)`.
+    pub upgraded: Vec<(&'a ModMetadata, &'a ModMetadata)>,
+    /// Mods whose version decreased, as `(old, new)`.
+    pub downgraded: Vec<(&'a ModMetadata, &'a ModMetadata)>,
+    pub dependency_changes: Vec<DependencyChanges<'a>>,
+}
+
+/// How the dependencies a mod declares changed between two versions.
+pub struct DependencyChanges<'a> {
+    pub mod_: &'a ModMetadata,
+    /// New dependencies, as `(dependency_id, range)`.
+    pub added: Vec<(String, String)>,
+    pub removed: Vec<String>,
+    /// Dependencies whose range or requirement changed, as `(dependency_id, old, new)`.
+    pub changed: Vec<(String, String, String)>,
+}
+
+impl ModSetDiff<'_> {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.upgraded.is_empty()
+            && self.downgraded.is_empty()
+            && self.dependency_changes.is_empty()
+    }
+}
+
+/// Loads the mods of `path`, which may be a mods directory, a modpack or a
+/// lockfile, keeping the entries of its loader for multi-loader jars.
+pub fn load_mod_set(path: &Path, options: &ScanOptions, loader: Option<&Platform>) -> Result<Vec<ModMetadata>> {
+    if !path.exists() {
+        anyhow::bail!("Not found: {}", path.display());
+    }
+
+    let (mods, environment_loader) = if path.is_file() && path.extension().is_some_and(|ext| ext == "lock") {
+        let lockfile = lock::read(path)?;
+        (lockfile.jars.into_iter().flat_map(|jar| jar.mods).collect::<Vec<_>>(), None)
+    } else {
+        let scanned = scan_dirs(&[path.to_path_buf()], options)?;
+        for (file_name, error) in &scanned.skipped {
+            eprintln!("[!] Skipping {} in {}: {}", file_name, path.display(), error);
+        }
+        (scanned.enabled, scanned.environment.loader.map(|(platform, _)| platform))
+    };
+
+    let loader = loader.cloned().or(environment_loader).or_else(|| detect_loader(&mods));
+    Ok(match loader {
+        Some(loader) => select_loader_entries(mods, &loader),
+        None => mods,
+    })
+}
+
+pub fn diff_mod_sets<'a>(old: &'a [ModMetadata], new: &'a [ModMetadata]) -> ModSetDiff<'a> {
+    let (old_by_id, new_by_id) = (newest_by_id(old), newest_by_id(new));
+    let mut diff = ModSetDiff::default();
+
+    for (id, old_mod) in &old_by_id {
+        let Some(new_mod) = new_by_id.get(id) else {
+            diff.removed.push(old_mod);
+            continue;
+        };
+        match compare_versions(&new_mod.version, &old_mod.version) {
+            std::cmp::Ordering::Greater => diff.upgraded.push((old_mod, new_mod)),
+            std::cmp::Ordering::Less => diff.downgraded.push((old_mod, new_mod)),
+            std::cmp::Ordering::Equal => {}
+        }
+        if let Some(changes) = dependency_changes(old_mod, new_mod) {
+            diff.dependency_changes.push(changes);
+        }
+    }
+    diff.added = new_by_id.iter()
+        .filter(|(id, _)| !old_by_id.contains_key(*id))
+        .map(|(_, mod_)| *mod_)
+        .collect();
+
+    diff
+}
+
+/// Indexes `mods` by id, keeping the newest of duplicated ids.
+fn newest_by_id(mods: &[ModMetadata]) -> BTreeMap<&str, &ModMetadata> {
+    let mut by_id: BTreeMap<&str, &ModMetadata> = BTreeMap::new();
+    for mod_ in mods {
+        let entry = by_id.entry(mod_.mod_id.as_str()).or_insert(mod_);
+        if compare_versions(&mod_.version, &entry.version).is_gt() {
+            *entry = mod_;
+        }
+    }
+    by_id
+}
+
+fn dependency_changes<'a>(old: &ModMetadata, new: &'a ModMetadata) -> Option<DependencyChanges<'a>> {
+    let describe = |mod_: &ModMetadata| -> BTreeMap<String, String> {
+        mod_.dependencies.iter()
+            .map(|dep| {
+                let requirement = if dep.mandatory { "requires" } else { "optional" };
+                (dep.mod_id.clone(), format!("{} {}", requirement, dep.version_range))
+            })
+            .collect()
+    };
+    let (old_deps, new_deps) = (describe(old), describe(new));
+
+    let changes = DependencyChanges {
+        mod_: new,
+        added: new_deps.iter()
+            .filter(|(id, _)| !old_deps.contains_key(*id))
+            .map(|(id, range)| (id.clone(), range.clone()))
+            .collect(),
+        removed: old_deps.keys().filter(|id| !new_deps.contains_key(*id)).cloned().collect(),
+        changed: old_deps.iter()
+            .filter_map(|(id, old_range)| {
+                let new_range = new_deps.get(id)?;
+                (new_range != old_range).then(|| (id.clone(), old_range.clone(), new_range.clone()))
+            })
+            .collect(),
+    };
+
+    (!changes.added.is_empty() || !changes.removed.is_empty() || !changes.changed.is_empty()).then_some(changes)
+}
+
+pub fn render(diff: &ModSetDiff, format: DiffFormat) -> String {
+    match format {
+        DiffFormat::Text => render_text(diff),
+        DiffFormat::Markdown => render_markdown(diff),
+    }
+}
+
+fn render_text(diff: &ModSetDiff) -> String {
+    let mut out = String::new();
+    if diff.is_empty() {
+        writeln!(out, "No changes").unwrap();
+        return out;
+    }
+
+    for mod_ in &diff.added {
+        writeln!(out, "+ {} {}", mod_.mod_id, mod_.version).unwrap();
+    }
+    for mod_ in &diff.removed {
+        writeln!(out, "- {} {}", mod_.mod_id, mod_.version).unwrap();
+    }
+    for (old, new) in &diff.upgraded {
+        writeln!(out, "↑ {} {} -> {}", new.mod_id, old.version, new.version).unwrap();
+    }
+    for (old, new) in &diff.downgraded {
+        writeln!(out, "↓ {} {} -> {}", new.mod_id, old.version, new.version).unwrap();
+    }
+    for changes in &diff.dependency_changes {
+        writeln!(out, "~ {} dependencies:", changes.mod_.mod_id).unwrap();
+        for line in dependency_change_lines(changes, |id| id.to_string()) {
+            writeln!(out, "    {}", line).unwrap();
+        }
+    }
+    out
+}
+
+fn render_markdown(diff: &ModSetDiff) -> String {
+    let mut out = String::new();
+    if diff.is_empty() {
+        writeln!(out, "No changes.").unwrap();
+        return out;
+    }
+
+    let title = |mod_: &ModMetadata| match &mod_.name {
+        Some(name) => format!("**{}** (`{}`)", name, mod_.mod_id),
+        None => format!("`{}`", mod_.mod_id),
+    };
+    let mut section = |heading: &str, lines: Vec<String>| {
+        if !lines.is_empty() {
+            writeln!(out, "### {}\n", heading).unwrap();
+            for line in lines {
+                writeln!(out, "- {}", line).unwrap();
+            }
+            writeln!(out).unwrap();
+        }
+    };
+
+    section("Added", diff.added.iter().map(|m| format!("{} {}", title(m), m.version)).collect());
+    section("Removed", diff.removed.iter().map(|m| format!("{} {}", title(m), m.version)).collect());
+    section(
+        "Updated",
+        diff.upgraded.iter().map(|(old, new)| format!("{}: {} → {}", title(new), old.version, new.version)).collect(),
+    );
+    section(
+        "Downgraded",
+        diff.downgraded.iter().map(|(old, new)| format!("{}: {} → {}", title(new), old.version, new.version)).collect(),
+    );
+    section(
+        "Dependency changes",
+        diff.dependency_changes.iter()
+            .map(|changes| format!(
+                "{}: {}",
+                title(changes.mod_),
+                dependency_change_lines(changes, |id| format!("`{}`", id)).join("; ")
+            ))
+            .collect(),
+    );
+    out
+}
+
+fn dependency_change_lines(changes: &DependencyChanges, id: impl Fn(&str) -> String) -> Vec<String> {
+    let mut lines = Vec::new();
+    for (dep, range) in &changes.added {
+        lines.push(format!("{} now {}", id(dep), range));
+    }
+    for dep in &changes.removed {
+        lines.push(format!("{} no longer needed", id(dep)));
+    }
+    for (dep, old, new) in &changes.changed {
+        lines.push(format!("{} {} -> {}", id(dep), old, new));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_mod_sets() {
+        let old = [
+            ModMetadata::new("kept", "1.0.0").with_dependencies(&[("lib", ">=1.0"), ("gone", "*")]),
+            ModMetadata::new("rolled_back", "2.0.0"),
+            ModMetadata::new("removed", "1.0.0"),
+            ModMetadata::new("lib", "1.0.0"),
+        ];
+        let new = [
+            ModMetadata::new("kept", "1.0.0").with_dependencies(&[("lib", ">=1.1"), ("api", "*")]),
+            ModMetadata::new("rolled_back", "1.9.0"),
+            ModMetadata::new("lib", "1.0.0"),
+            ModMetadata::new("lib", "1.1.0"),
+            ModMetadata::new("added", "1.0.0"),
+        ];
+
+        let diff = diff_mod_sets(&old, &new);
+        let ids = |mods: &[&ModMetadata]| mods.iter().map(|m| m.mod_id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&diff.added), vec!["added"]);
+        assert_eq!(ids(&diff.removed), vec!["removed"]);
+        assert_eq!(diff.upgraded.iter().map(|(_, new)| new.version.as_str()).collect::<Vec<_>>(), vec!["1.1.0"]);
+        assert_eq!(diff.downgraded.iter().map(|(_, new)| new.mod_id.as_str()).collect::<Vec<_>>(), vec!["rolled_back"]);
+
+        assert_eq!(diff.dependency_changes.len(), 1);
+        let changes = &diff.dependency_changes[0];
+        assert_eq!(changes.added, vec![("api".to_string(), "requires *".to_string())]);
+        assert_eq!(changes.removed, vec!["gone".to_string()]);
+        assert_eq!(changes.changed, vec![("lib".to_string(), "requires >=1.0".to_string(), "requires >=1.1".to_string())]);
+    }
+}
@@ -0,0 +1,74 @@
+use std::path::{Path, PathBuf};
+
+/// What can go wrong reading jars, modpacks and their metadata.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// A file or directory couldn't be read or written.
+    #[error("Failed to {action} {}", path.display())]
+    Io {
+        action: &'static str,
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    /// A jar or modpack isn't a valid ZIP archive, or lacks an entry.
+    #[error("Invalid ZIP/JAR format: {name}")]
+    Archive {
+        name: String,
+        #[source]
+        source: zip::result::ZipError,
+    },
+    /// A metadata, index or manifest file is malformed or lacks a required field.
+    #[error("Invalid {file} in {name}")]
+    Metadata {
+        name: String,
+        file: String,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// A jar has no metadata of any supported loader.
+    #[error("Unsupported mod file format in '{name}'. Expected one of: {}", expected.join(", "))]
+    UnsupportedFormat {
+        name: String,
+        /// The formats of the parsers that were tried.
+        expected: Vec<String>,
+    },
+    /// An include or exclude pattern isn't a valid glob.
+    #[error("Invalid glob pattern: {pattern}")]
+    Glob {
+        pattern: String,
+        #[source]
+        source: globset::Error,
+    },
+    /// A modpack couldn't be read.
+    #[error("Failed to read modpack {}", path.display())]
+    Pack {
+        path: PathBuf,
+        #[source]
+        source: Box<Error>,
+    },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl Error {
+    pub fn io(action: &'static str, path: &Path, source: std::io::Error) -> Self {
+        Error::Io { action, path: path.to_path_buf(), source }
+    }
+
+    pub fn archive(name: impl Into<String>, source: impl Into<zip::result::ZipError>) -> Self {
+        Error::Archive { name: name.into(), source: source.into() }
+    }
+
+    pub fn metadata(
+        name: impl Into<String>,
+        file: impl Into<String>,
+        source: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+    ) -> Self {
+        Error::Metadata { name: name.into(), file: file.into(), source: source.into() }
+    }
+
+    pub fn pack(path: &Path, source: Error) -> Self {
+        Error::Pack { path: path.to_path_buf(), source: Box::new(source) }
+    }
+}
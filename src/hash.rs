@@ -0,0 +1,136 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use md5::Md5;
+use sha1::{Digest, Sha1};
+use sha2::{Sha256, Sha512};
+use crate::error::{Error, Result};
+
+/// The hash algorithms mod platforms and pack formats use to identify files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashFormat {
+    Sha1,
+    Sha256,
+    Sha512,
+    Md5,
+}
+
+impl std::str::FromStr for HashFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "sha1" => Ok(HashFormat::Sha1),
+            "sha256" => Ok(HashFormat::Sha256),
+            "sha512" => Ok(HashFormat::Sha512),
+            "md5" => Ok(HashFormat::Md5),
+            _ => Err(format!("Unsupported hash format: {}", s)),
+        }
+    }
+}
+
+/// Hashes the contents of `path`, as lowercase hex.
+pub fn hash_file(path: &Path, format: HashFormat) -> Result<String> {
+    let mut file = File::open(path)
+        .map_err(|e| Error::io("open", path, e))?;
+    let mut contents = Vec::new();
+    file.read_to_end(&mut contents)
+        .map_err(|e| Error::io("read", path, e))?;
+    Ok(hash_bytes(&contents, format))
+}
+
+/// The hashes mod platforms identify a jar by.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileHashes {
+    /// Identifies the file on Modrinth, along with `sha512`.
+    pub sha1: String,
+    pub sha512: String,
+    /// Identifies the file on CurseForge, see `curseforge_fingerprint`.
+    pub curseforge_fingerprint: u32,
+}
+
+impl FileHashes {
+    pub fn of(bytes: &[u8]) -> Self {
+        FileHashes {
+            sha1: hash_bytes(bytes, HashFormat::Sha1),
+            sha512: hash_bytes(bytes, HashFormat::Sha512),
+            curseforge_fingerprint: curseforge_fingerprint(bytes),
+        }
+    }
+}
+
+pub fn hash_bytes(bytes: &[u8], format: HashFormat) -> String {
+    match format {
+        HashFormat::Sha1 => hex::encode(Sha1::digest(bytes)),
+        HashFormat::Sha256 => hex::encode(Sha256::digest(bytes)),
+        HashFormat::Sha512 => hex::encode(Sha512::digest(bytes)),
+        HashFormat::Md5 => hex::encode(Md5::digest(bytes)),
+    }
+}
+
+/// The fingerprint CurseForge identifies files by: the 32-bit MurmurHash2,
+/// seeded with 1, of the contents stripped of tabs, newlines and spaces.
+pub fn curseforge_fingerprint(bytes: &[u8]) -> u32 {
+    let stripped: Vec<u8> = bytes.iter()
+        .copied()
+        .filter(|b| !matches!(b, b'\t' | b'\n' | b'\r' | b' '))
+        .collect();
+    murmur2(&stripped, 1)
+}
+
+// https://github.com/aappleby/smhasher/blob/master/src/MurmurHash2.cpp
+fn murmur2(data: &[u8], seed: u32) -> u32 {
+    const M: u32 = 0x5bd1e995;
+    const R: u32 = 24;
+
+    let mut h = seed ^ data.len() as u32;
+    let mut chunks = data.chunks_exact(4);
+    for chunk in &mut chunks {
+        let mut k = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+        h = h.wrapping_mul(M);
+        h ^= k;
+    }
+
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        for (i, byte) in tail.iter().enumerate() {
+            h ^= (*byte as u32) << (8 * i);
+        }
+        h = h.wrapping_mul(M);
+    }
+
+    h ^= h >> 13;
+    h = h.wrapping_mul(M);
+    h ^= h >> 15;
+    h
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_bytes() {
+        assert_eq!(hash_bytes(b"abc", HashFormat::Sha1), "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(
+            hash_bytes(b"abc", HashFormat::Sha256),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(hash_bytes(b"abc", HashFormat::Md5), "900150983cd24fb0d6963f7d28e17f72");
+    }
+
+    #[test]
+    fn test_curseforge_fingerprint() {
+        // Expected values from the reference MurmurHash2, seeded with 1, of the stripped input.
+        assert_eq!(murmur2(b"", 1), 0x5bd15e36);
+        // Two blocks and a 2-byte tail
+        assert_eq!(curseforge_fingerprint(b"hello world"), 0xa85cbded);
+        // Three blocks and a 3-byte tail
+        assert_eq!(curseforge_fingerprint(b"mmod\tfingerprint\r\n"), 0xc36862cc);
+        // Blocks only
+        assert_eq!(curseforge_fingerprint(b"abcd efgh"), 0x4065991a);
+    }
+}
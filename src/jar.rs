@@ -1,14 +1,57 @@
 use std::{
     fs::File,
+    io::{Read, Seek},
     path::Path,
 };
 use zip::ZipArchive;
-use anyhow::{Result, Context};
+use crate::error::{Error, Result};
 
-pub fn open_jar_file(jar_path: &Path) -> Result<ZipArchive<File>> {
+/// The entries of a jar, as read by a `ModParser`.
+pub trait Archive {
+    /// Whether the jar has an entry at `name`, e.g. `META-INF/mods.toml`.
+    fn contains(&mut self, name: &str) -> bool;
+    /// Reads the entry at `name`.
+    fn read(&mut self, name: &str) -> std::io::Result<Vec<u8>>;
+}
+
+impl<R: Read + Seek> Archive for ZipArchive<R> {
+    fn contains(&mut self, name: &str) -> bool {
+        self.index_for_name(name).is_some()
+    }
+
+    fn read(&mut self, name: &str) -> std::io::Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.by_name(name)?.read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+}
+
+/// Opens the jar at `jar_path`.
+pub(crate) fn open_jar_file(jar_path: &Path) -> Result<ZipArchive<File>> {
     let file = File::open(jar_path)
-        .with_context(|| format!("Failed to open JAR file: {}", jar_path.display()))?;
+        .map_err(|e| Error::io("open", jar_path, e))?;
 
     ZipArchive::new(file)
-        .with_context(|| format!("Invalid ZIP/JAR format: {}", jar_path.display()))
-}
\ No newline at end of file
+        .map_err(|e| Error::archive(jar_path.display().to_string(), e))
+}
+
+/// Reads the entry `name` of the jar named `file_name` as text.
+pub fn read_entry(archive: &mut (impl Archive + ?Sized), name: &str, file_name: &str) -> Result<String> {
+    let bytes = archive.read(name)
+        .map_err(|e| Error::archive(file_name, e))?;
+    String::from_utf8(bytes)
+        .map_err(|e| Error::metadata(file_name, name, e))
+}
+
+/// Reads a main attribute of the jar's `META-INF/MANIFEST.MF`, if both exist.
+pub fn manifest_attribute(archive: &mut (impl Archive + ?Sized), name: &str) -> Option<String> {
+    let contents = String::from_utf8(archive.read("META-INF/MANIFEST.MF").ok()?).ok()?;
+
+    contents.lines()
+        .take_while(|line| !line.trim().is_empty())
+        .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+        .map(|value| value.trim().to_string())
+}
+
+#[cfg(test)]
+pub(crate) mod test_util;
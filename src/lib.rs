@@ -0,0 +1,50 @@
+//! Reads the metadata of Minecraft mod jars (Fabric, Forge and NeoForge) and
+//! finds what a set of mods is missing: dependencies, compatible versions,
+//! the right loader or game version.
+//!
+//! Jars can be parsed one at a time, from a path or from bytes, or every jar
+//! of a mods directory or modpack can be scanned at once:
+//!
+//! ```no_run
+//! use std::path::PathBuf;
+//! use mmod::{detect_loader, diagnose, scan_dirs, ScanOptions};
+//!
+//! # fn main() -> mmod::Result<()> {
+//! let options = ScanOptions::new(false, &[], &[])?;
+//! let scanned = scan_dirs(&[PathBuf::from("mods")], &options)?;
+//! for (file_name, error) in &scanned.skipped {
+//!     eprintln!("Skipping {}: {}", file_name, error);
+//! }
+//!
+//! let loader = detect_loader(&scanned.enabled);
+//! for error in diagnose(&scanned.enabled, loader.as_ref(), &scanned.environment) {
+//!     println!("{}", error);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Jars are read by the `ModParser`s of a `ParserRegistry`. Other metadata
+//! formats can be read by registering a parser of their own in `ScanOptions::parsers`,
+//! with the helpers of `jar` and the `Error` constructors.
+
+mod error;
+mod r#mod;
+mod scan;
+pub mod hash;
+pub mod jar;
+pub mod pack;
+
+pub use error::{Error, Result};
+pub use jar::Archive;
+pub use r#mod::{
+    analyze_dependencies, check_environment, compare_versions, dependency_graph, detect_loader, diagnose,
+    find_orphans, is_platform_dependency, select_loader_entries, suggest_fixes,
+    DependencyError, DependencyErrors, DependencyGraph, DependencyVersionRange, Direction, Edge, Environment,
+    FabricParser, Fix, ForgeParser, Likeness, ModDependency, ModMetadata, ModParser, NeoForgeParser,
+    ParserRegistry, Platform, Severity, Side, Suggestion,
+};
+pub use scan::{
+    parse_jar_bytes, parse_mod_archive, parse_mod_file, scan_dirs, ModFile, ScanOptions, ScannedMods,
+    DISABLED_SUFFIX,
+};
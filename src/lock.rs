@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use mmod::{ModFile, ModMetadata};
+
+pub const LOCK_FILE: &str = "mmod.lock";
+const LOCK_VERSION: u32 = 1;
+
+/// A snapshot of the jars of a mods directory, written to `mmod.lock`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Lockfile {
+    pub version: u32,
+    #[serde(default)]
+    pub jars: Vec<LockedJar>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LockedJar {
+    pub file_name: String,
+    pub size: u64,
+    pub sha1: String,
+    pub sha512: String,
+    /// Every mod the jar declares, for every platform it ships metadata for.
+    pub mods: Vec<ModMetadata>,
+}
+
+impl LockedJar {
+    /// The mods of the jar as `id version` pairs.
+    fn mod_versions(&self) -> Vec<String> {
+        self.mods.iter().map(|m| format!("{} {}", m.mod_id, m.version)).collect()
+    }
+}
+
+/// How a jar differs from the lockfile.
+#[derive(Debug, PartialEq)]
+pub enum Change {
+    Added(String),
+    Removed(String),
+    /// A jar whose contents changed along with the versions of its mods, as
+    /// `(file_name, locked, current)`.
+    Modified(String, Vec<String>, Vec<String>),
+    /// A jar whose contents changed while its mods claim the same versions.
+    Tampered(String),
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Change::Added(file_name) => write!(f, "added: {}", file_name),
+            Change::Removed(file_name) => write!(f, "removed: {}", file_name),
+            Change::Modified(file_name, locked, current) => write!(
+                f,
+                "modified: {} ({} -> {})",
+                file_name, locked.join(", "), current.join(", ")
+            ),
+            Change::Tampered(file_name) => write!(
+                f,
+                "tampered: {} (contents changed, but the mod versions are the same)",
+                file_name
+            ),
+        }
+    }
+}
+
+/// Snapshots every jar of `mods` found in `files`.
+pub fn lock(mods: &[ModMetadata], files: &HashMap<String, ModFile>) -> Result<Lockfile> {
+    let mut by_file: HashMap<&str, Vec<ModMetadata>> = HashMap::new();
+    for mod_ in mods {
+        by_file.entry(mod_.file_name.as_str()).or_default().push(mod_.clone());
+    }
+
+    let mut jars = Vec::new();
+    for (file_name, mods) in by_file {
+        let Some(file) = files.get(file_name) else { continue };
+        let hashes = file.hashes()?;
+        jars.push(LockedJar {
+            file_name: file_name.to_string(),
+            size: file.size,
+            sha1: hashes.sha1.clone(),
+            sha512: hashes.sha512.clone(),
+            mods,
+        });
+    }
+    jars.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+
+    Ok(Lockfile { version: LOCK_VERSION, jars })
+}
+
+pub fn read(path: &Path) -> Result<Lockfile> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let lockfile: Lockfile = toml::from_str(&contents)
+        .with_context(|| format!("Invalid lockfile {}", path.display()))?;
+    if lockfile.version != LOCK_VERSION {
+        anyhow::bail!("Unsupported lockfile version {} in {}", lockfile.version, path.display());
+    }
+    Ok(lockfile)
+}
+
+pub fn write(path: &Path, lockfile: &Lockfile) -> Result<()> {
+    let contents = format!(
+        "# Generated by mmod, do not edit. Check the jars against it with `mmod verify`.\n{}",
+        toml::to_string(lockfile)?
+    );
+    std::fs::write(path, contents)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Compares the `current` jars with the `locked` ones, by file name.
+pub fn verify(locked: &Lockfile, current: &Lockfile) -> Vec<Change> {
+    let locked_jars: HashMap<_, _> = locked.jars.iter().map(|jar| (jar.file_name.as_str(), jar)).collect();
+    let current_jars: HashMap<_, _> = current.jars.iter().map(|jar| (jar.file_name.as_str(), jar)).collect();
+
+    let mut changes = Vec::new();
+    for jar in &locked.jars {
+        let Some(current) = current_jars.get(jar.file_name.as_str()) else {
+            changes.push(Change::Removed(jar.file_name.clone()));
+            continue;
+        };
+        if current.size == jar.size && current.sha1 == jar.sha1 && current.sha512 == jar.sha512 {
+            continue;
+        }
+        if current.mod_versions() == jar.mod_versions() {
+            changes.push(Change::Tampered(jar.file_name.clone()));
+        } else {
+            changes.push(Change::Modified(jar.file_name.clone(), jar.mod_versions(), current.mod_versions()));
+        }
+    }
+    for jar in &current.jars {
+        if !locked_jars.contains_key(jar.file_name.as_str()) {
+            changes.push(Change::Added(jar.file_name.clone()));
+        }
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locked_jar(file_name: &str, version: &str, sha1: &str) -> LockedJar {
+        LockedJar {
+            file_name: file_name.to_string(),
+            size: 1,
+            sha1: sha1.to_string(),
+            sha512: "sha512".to_string(),
+            mods: vec![ModMetadata::new(file_name.trim_end_matches(".jar"), version).with_file_name(file_name)],
+        }
+    }
+
+    #[test]
+    fn test_verify() {
+        let locked = Lockfile {
+            version: LOCK_VERSION,
+            jars: vec![
+                locked_jar("same.jar", "1.0.0", "a"),
+                locked_jar("updated.jar", "1.0.0", "b"),
+                locked_jar("tampered.jar", "1.0.0", "c"),
+                locked_jar("removed.jar", "1.0.0", "d"),
+            ],
+        };
+        let current = Lockfile {
+            version: LOCK_VERSION,
+            jars: vec![
+                locked_jar("same.jar", "1.0.0", "a"),
+                locked_jar("updated.jar", "1.1.0", "e"),
+                locked_jar("tampered.jar", "1.0.0", "f"),
+                locked_jar("added.jar", "1.0.0", "g"),
+            ],
+        };
+
+        assert_eq!(verify(&locked, &current), vec![
+            Change::Modified(
+                "updated.jar".to_string(),
+                vec!["updated 1.0.0".to_string()],
+                vec!["updated 1.1.0".to_string()],
+            ),
+            Change::Tampered("tampered.jar".to_string()),
+            Change::Removed("removed.jar".to_string()),
+            Change::Added("added.jar".to_string()),
+        ]);
+
+        let round_trip: Lockfile = toml::from_str(&toml::to_string(&current).unwrap()).unwrap();
+        assert!(verify(&current, &round_trip).is_empty());
+    }
+}
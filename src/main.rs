@@ -1,91 +1,525 @@
-mod jar;
-mod r#mod;
+mod why;
+mod tree;
+mod diagram;
+mod report;
+mod simulate;
+mod toggle;
+mod lock;
+mod diff;
+mod resolve;
+#[cfg(feature = "modrinth")]
+mod modrinth;
 
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use clap::Parser;
-use anyhow::Result;
-use crate::r#mod::{ModMetadata, parse_forge_mod_contents, parse_fabric_mod_contents, parse_neoforge_mod_contents, analyze_dependencies};
+use clap::{Parser, Subcommand, ValueEnum};
+use anyhow::{Context, Result};
+use mmod::{pack, DependencyErrors, ModFile, Platform, ScanOptions, dependency_graph, detect_loader, diagnose, find_orphans, parse_mod_file, scan_dirs, select_loader_entries, suggest_fixes};
+#[cfg(feature = "modrinth")]
+use mmod::{analyze_dependencies, DependencyError, ModMetadata};
 
 #[derive(Parser)]
 #[command(name = "Minecraft MODs Dependency Analyzer")]
 #[command(version, about, long_about = None)]
 struct Cli {
+    /// The mods directory, a Modrinth `.mrpack` or CurseForge modpack zip,
+    /// the `minecraftinstance.json` of a CurseForge instance, or a packwiz `pack.toml`
     #[arg(default_value = "./")]
     dir: PathBuf,
-    #[arg(long, action)]
-    verbose: bool
+    #[arg(long, action, global = true)]
+    verbose: bool,
+    /// The loader of the instance (forge, fabric, neoforge or quilt).
+    /// Detected from the mods when omitted.
+    #[arg(long, global = true)]
+    loader: Option<Platform>,
+    /// Analyze the jars of this directory together with DIR (can be repeated)
+    #[arg(long = "add-dir", value_name = "DIR", global = true)]
+    add_dirs: Vec<PathBuf>,
+    /// Also scan subdirectories, e.g. `mods/<mcversion>/`
+    #[arg(long, short, action, global = true)]
+    recursive: bool,
+    /// Only load jars whose path relative to their directory matches this glob (can be repeated)
+    #[arg(long, value_name = "GLOB", global = true)]
+    include: Vec<String>,
+    /// Skip jars whose path relative to their directory matches this glob (can be repeated)
+    #[arg(long, value_name = "GLOB", global = true)]
+    exclude: Vec<String>,
+    /// A directory of candidate jars, e.g. a cache with many versions of each mod,
+    /// to pick versions from. Scanned recursively
+    #[arg(long, value_name = "DIR", global = true)]
+    repo: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(flatten)]
+    Instance(InstanceCommand),
+    /// Compare two mod sets, each a mods directory, a lockfile or a modpack
+    Diff {
+        old: PathBuf,
+        new: PathBuf,
+        #[arg(long, value_enum, default_value = "text")]
+        format: diff::DiffFormat,
+    },
+}
+
+// The subcommands that analyze the mods of DIR.
+#[derive(Subcommand)]
+enum InstanceCommand {
+    /// Show which mods depend on a mod, directly or through other mods
+    Why {
+        mod_id: String,
+    },
+    /// Print the dependency tree of a mod, or of every top-level mod
+    Tree {
+        mod_id: Option<String>,
+        /// Maximum depth of the tree
+        #[arg(long)]
+        depth: Option<usize>,
+        /// Show the mods that depend on each mod instead of its dependencies
+        #[arg(long, short, action)]
+        invert: bool,
+    },
+    /// Export the dependency graph as a diagram
+    Graph {
+        #[arg(long, value_enum, default_value = "dot")]
+        format: diagram::DiagramFormat,
+    },
+    /// List library mods that nothing depends on anymore
+    Orphans {
+        /// Also treat this mod id as a library (can be repeated)
+        #[arg(long = "library", value_name = "MOD_ID")]
+        libraries: Vec<String>,
+    },
+    /// Show how removing or adding jars would change the diagnostics, without touching any file
+    Simulate {
+        /// Remove the jar containing this mod id, or with this file name (can be repeated)
+        #[arg(long, value_name = "MOD_ID|FILE")]
+        remove: Vec<String>,
+        /// Add this jar (can be repeated)
+        #[arg(long, value_name = "PATH")]
+        add: Vec<PathBuf>,
+    },
+    /// Disable the jar of a mod by renaming it to `*.jar.disabled`
+    Disable {
+        mod_id: String,
+        /// Also disable the mods that would miss it as a mandatory dependency
+        #[arg(long, action)]
+        with_dependents: bool,
+        /// Also disable the library mods that only the disabled mods depended on
+        #[arg(long, action)]
+        with_orphans: bool,
+        /// Also treat this mod id as a library for --with-orphans (can be repeated)
+        #[arg(long = "library", value_name = "MOD_ID")]
+        libraries: Vec<String>,
+        /// Disable even if other mods would miss a mandatory dependency
+        #[arg(long, action)]
+        force: bool,
+    },
+    /// Enable a disabled jar by removing its `.disabled` suffix
+    Enable {
+        mod_id: String,
+        /// Also enable the disabled mods it needs as mandatory dependencies
+        #[arg(long, action)]
+        with_dependencies: bool,
+        /// Enable even if a mandatory dependency would be missing
+        #[arg(long, action)]
+        force: bool,
+    },
+    /// Describe the jars in a modpack format
+    Export {
+        #[arg(value_enum)]
+        format: ExportFormat,
+        /// The directory of the pack to write to
+        #[arg(long, default_value = "./")]
+        output: PathBuf,
+    },
+    /// Write `mmod.lock`, a snapshot of every jar with its hashes and metadata
+    Lock {
+        /// Where to write the lockfile [default: DIR/mmod.lock]
+        #[arg(long)]
+        file: Option<PathBuf>,
+    },
+    /// Check the jars against `mmod.lock`, reporting added, removed, modified or tampered jars
+    Verify {
+        /// The lockfile to check against [default: DIR/mmod.lock]
+        #[arg(long)]
+        file: Option<PathBuf>,
+    },
+    /// Pick a version of each mod among the jars of --repo and DIR so that every dependency is satisfied
+    Resolve,
+    /// Look jars and missing dependencies up on Modrinth
+    #[cfg(feature = "modrinth")]
+    Modrinth {
+        #[command(subcommand)]
+        action: ModrinthAction,
+        /// Base URL of the Modrinth API
+        #[arg(long, default_value = modrinth::DEFAULT_BASE_URL)]
+        api_url: String,
+    },
+    /// Write a report of the mods and their problems
+    Report {
+        /// Write a self-contained HTML page to this file
+        #[arg(long)]
+        html: PathBuf,
+    },
+}
+
+#[cfg(feature = "modrinth")]
+#[derive(Subcommand)]
+enum ModrinthAction {
+    /// Show the Modrinth project and version of each jar
+    Identify,
+    /// Download compatible versions of missing dependencies into DIR
+    Fetch {
+        /// The Minecraft version to download for [default: the pack's, or the newest all identified jars support]
+        #[arg(long)]
+        game_version: Option<String>,
+        /// Download without asking for confirmation
+        #[arg(long, short, action)]
+        yes: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    /// A packwiz pack: a `.pw.toml` per jar, listed in `index.toml`
+    Packwiz,
+}
+
+impl From<ExportFormat> for pack::ExportFormat {
+    fn from(format: ExportFormat) -> Self {
+        match format {
+            ExportFormat::Packwiz => pack::ExportFormat::Packwiz,
+        }
+    }
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
-    let mods_dir = cli.dir.as_path();
     let verbose = cli.verbose;
+    let options = ScanOptions::new(cli.recursive, &cli.include, &cli.exclude)?;
+
+    let command = match &cli.command {
+        Some(Command::Instance(command)) => Some(command),
+        // Compares mod sets of its own rather than the one of DIR.
+        Some(Command::Diff { old, new, format }) => {
+            let old = diff::load_mod_set(old, &options, cli.loader.as_ref())?;
+            let new = diff::load_mod_set(new, &options, cli.loader.as_ref())?;
+            print!("{}", diff::render(&diff::diff_mod_sets(&old, &new), *format));
+            return Ok(());
+        }
+        None => None,
+    };
+
+    let mut dirs = vec![cli.dir.clone()];
+    dirs.extend(cli.add_dirs.iter().cloned());
+    for dir in &dirs {
+        if !dir.exists() {
+            anyhow::bail!("Mods directory not found: {}", dir.display());
+        }
+    }
+
+    let scanned = scan_dirs(&dirs, &options)?;
+    let mut mods = scanned.enabled.clone();
+    let mut disabled = scanned.disabled.clone();
+    let environment = scanned.environment.clone();
 
-    if !mods_dir.exists() {
-        anyhow::bail!("Mods directory not found: {}", mods_dir.display());
+    if let Some(minecraft) = &environment.minecraft {
+        eprintln!("[✓] Target Minecraft version: {}", minecraft);
+    }
+    if let Some((platform, version)) = &environment.loader {
+        eprintln!("[✓] Target loader version: {:?} {}", platform, version);
     }
 
-    let mods = load_mods_from_dir(mods_dir)?;
+    let loader = cli.loader
+        .or_else(|| environment.loader.as_ref().map(|(platform, _)| platform.clone()))
+        .or_else(|| detect_loader(&mods));
+    match &loader {
+        Some(loader) => {
+            eprintln!("[✓] Instance loader: {:?}", loader);
+            mods = select_loader_entries(mods, loader);
+            disabled = select_loader_entries(disabled, loader);
+        }
+        None => eprintln!("[!] Could not determine the instance loader"),
+    }
 
-    println!("[✓] {} mods analyzed", mods.len());
+    // Status goes to stderr so that the output of subcommands can be redirected.
+    eprintln!("[✓] {} mods analyzed", mods.len());
 
     if verbose{
         for mod_data in mods.iter() {
-            println!("  - {}", mod_data.mod_id);
+            eprintln!("  - {}", mod_data.mod_id);
+        }
+    }
+
+    if !disabled.is_empty() {
+        eprintln!("[-] {} disabled mods, not analyzed", disabled.len());
+        if verbose {
+            for mod_data in disabled.iter() {
+                eprintln!("  - {} ({})", mod_data.mod_id, mod_data.file_name);
+            }
+        }
+    }
+
+    if !scanned.unavailable.is_empty() {
+        eprintln!(
+            "[!] {} jars of the pack are not available locally, their metadata is unavailable offline",
+            scanned.unavailable.len()
+        );
+        if verbose {
+            for path in scanned.unavailable.iter() {
+                eprintln!("  - {}", path);
+            }
         }
     }
 
-    match analyze_dependencies(&mods) {
-        Ok(ordered) => println!("All dependencies are satisfied!"),
-        Err(e) => {
-            eprintln!("Dependency error: {}", e);
+    for warning in &scanned.warnings {
+        eprintln!("[!] {}", warning);
+    }
+    for (file_name, error) in &scanned.skipped {
+        eprintln!("[!] Skipping {}: {}", file_name, error);
+    }
+
+    match command {
+        Some(InstanceCommand::Why { mod_id }) => {
+            why::print_why(&dependency_graph(&mods, loader.as_ref()), mod_id)?;
+        }
+        Some(InstanceCommand::Tree { mod_id, depth, invert }) => {
+            let options = tree::TreeOptions { depth: *depth, invert: *invert };
+            tree::print_tree(&dependency_graph(&mods, loader.as_ref()), mod_id.as_deref(), &options)?;
+        }
+        Some(InstanceCommand::Graph { format }) => {
+            print!("{}", diagram::render(&dependency_graph(&mods, loader.as_ref()), *format));
+        }
+        Some(InstanceCommand::Orphans { libraries }) => {
+            let graph = dependency_graph(&mods, loader.as_ref());
+            let orphans = find_orphans(&graph, libraries);
+            if orphans.is_empty() {
+                println!("No unused library mods found");
+            } else {
+                println!("Library mods nothing depends on, safe to remove:");
+                for mod_ in orphans {
+                    println!("  - {} {} ({})", mod_.mod_id, mod_.version, mod_.file_name);
+                }
+            }
+        }
+        Some(InstanceCommand::Simulate { remove, add }) => {
+            let mut added = Vec::new();
+            for path in add {
+                added.extend(parse_mod_file(path)?);
+            }
+            let mut modified = simulate::modified_mod_set(&mods, remove, added)?;
+            if let Some(loader) = &loader {
+                modified = select_loader_entries(modified, loader);
+            }
+
+            let before = diagnose(&mods, loader.as_ref(), &environment);
+            let after = diagnose(&modified, loader.as_ref(), &environment);
+            simulate::print_diff(&before, &after);
+        }
+        Some(InstanceCommand::Disable { mod_id, with_dependents, with_orphans, libraries, force }) => {
+            let plan = toggle::plan_disable(&mods, mod_id, loader.as_ref(), *with_dependents, *with_orphans, libraries)?;
+            apply_toggle(&scanned.files, &plan, true, *force)?;
+        }
+        Some(InstanceCommand::Enable { mod_id, with_dependencies, force }) => {
+            let plan = toggle::plan_enable(&mods, &disabled, mod_id, loader.as_ref(), *with_dependencies)?;
+            apply_toggle(&scanned.files, &plan, false, *force)?;
+        }
+        Some(InstanceCommand::Export { format, output }) => {
+            let exported = pack::export((*format).into(), &mods, &scanned.files, &scanned.origins, output)?;
+            for path in &exported.incomplete {
+                eprintln!("[!] The download source of {} is unknown, fill in its URL", path.display());
+            }
+            println!("[✓] {} files written to {}", exported.written.len(), output.display());
+        }
+        Some(InstanceCommand::Lock { file }) => {
+            let file = file.clone().unwrap_or_else(|| lockfile_path(&cli.dir));
+            let all: Vec<_> = scanned.enabled.iter().chain(scanned.disabled.iter()).cloned().collect();
+            let lockfile = lock::lock(&all, &scanned.files)?;
+            lock::write(&file, &lockfile)?;
+            println!("[✓] {} jars locked in {}", lockfile.jars.len(), file.display());
+        }
+        Some(InstanceCommand::Verify { file }) => {
+            let file = file.clone().unwrap_or_else(|| lockfile_path(&cli.dir));
+            let locked = lock::read(&file)?;
+            let all: Vec<_> = scanned.enabled.iter().chain(scanned.disabled.iter()).cloned().collect();
+            let changes = lock::verify(&locked, &lock::lock(&all, &scanned.files)?);
+            if changes.is_empty() {
+                println!("[✓] All jars match {}", file.display());
+            } else {
+                for change in &changes {
+                    println!("  {}", change);
+                }
+                anyhow::bail!("{} jars differ from {}", changes.len(), file.display());
+            }
+        }
+        Some(InstanceCommand::Resolve) => {
+            let Some(repo) = &cli.repo else {
+                anyhow::bail!("No candidate directory to pick versions from, set it with --repo");
+            };
+            let repo_options = ScanOptions::new(true, &cli.include, &cli.exclude)?;
+            let (candidates, skipped) = resolve::load_candidates(repo, &repo_options, loader.as_ref(), &environment)?;
+            eprintln!("[✓] Loaded {} candidate mods from {}", candidates.len(), repo.display());
+            if skipped > 0 {
+                eprintln!("[!] Skipped {} mods for another loader or game version", skipped);
+            }
+
+            let mut required: Vec<String> = mods.iter().map(|mod_| mod_.mod_id.clone()).collect();
+            required.sort();
+            required.dedup();
+            let candidates: Vec<_> = mods.iter().cloned().chain(candidates).collect();
+            match resolve::resolve(&required, &candidates) {
+                Ok(selected) => resolve::print_selection(&selected, &mods),
+                Err(unsatisfiable) => {
+                    println!("{}", unsatisfiable);
+                    anyhow::bail!("No version of each mod satisfies every dependency");
+                }
+            }
+        }
+        #[cfg(feature = "modrinth")]
+        Some(InstanceCommand::Modrinth { action, api_url }) => {
+            let client = modrinth::ModrinthClient::new(api_url);
+            let identified = modrinth::identify(&client, &scanned.files)?;
+            match action {
+                ModrinthAction::Identify => {
+                    let mut file_names: Vec<_> = scanned.files.keys().collect();
+                    file_names.sort();
+                    for file_name in file_names {
+                        match identified.get(file_name) {
+                            Some(version) => println!(
+                                "{}: project {}, version {} ({})",
+                                file_name, version.project_id, version.version_number, version.id
+                            ),
+                            None => println!("{}: not found on Modrinth", file_name),
+                        }
+                    }
+                }
+                ModrinthAction::Fetch { game_version, yes } => {
+                    let game_version = game_version.clone()
+                        .or_else(|| environment.minecraft.clone())
+                        .or_else(|| modrinth::common_game_version(identified.values()));
+                    fetch_missing(&client, &cli.dir, &mods, loader.as_ref(), game_version.as_deref(), *yes)?;
+                }
+            }
+        }
+        Some(InstanceCommand::Report { html }) => {
+            let errors = diagnose(&mods, loader.as_ref(), &environment);
+            let page = report::render_html(&mods, &errors, &dependency_graph(&mods, loader.as_ref()), &scanned.origins);
+            std::fs::write(html, page)
+                .with_context(|| format!("Failed to write report to {}", html.display()))?;
+            println!("[✓] Report written to {}", html.display());
+        }
+        None => {
+            let errors = diagnose(&mods, loader.as_ref(), &environment);
+            if errors.is_empty() {
+                println!("All dependencies are satisfied!");
+            } else {
+                let candidates = match &cli.repo {
+                    Some(repo) => {
+                        let repo_options = ScanOptions::new(true, &cli.include, &cli.exclude)?;
+                        Some(resolve::load_candidates(repo, &repo_options, loader.as_ref(), &environment)?.0)
+                    }
+                    None => None,
+                };
+                let fixes = suggest_fixes(&mods, &errors, loader.as_ref(), candidates.as_deref());
+                eprintln!("Dependency error: {}", DependencyErrors(errors));
+                if !fixes.is_empty() {
+                    eprintln!("Suggested fixes:");
+                    for fix in fixes {
+                        eprintln!("  - {}", fix);
+                    }
+                }
+            }
         }
     }
 
     Ok(())
 }
 
-fn parse_mod_file(path: &Path) -> Result<Vec<ModMetadata>> {
-    let file_name = path.file_name().unwrap().to_string_lossy().into_owned();
-
-    let mut archive = jar::open_jar_file(path)?;
+/// Downloads, after confirmation, the newest Modrinth versions of the mods
+/// `mods` miss that are compatible with the instance.
+#[cfg(feature = "modrinth")]
+fn fetch_missing(
+    client: &modrinth::ModrinthClient,
+    dir: &Path,
+    mods: &[ModMetadata],
+    loader: Option<&Platform>,
+    game_version: Option<&str>,
+    yes: bool,
+) -> Result<()> {
+    if !dir.is_dir() {
+        anyhow::bail!("Can only download into a mods directory, not {}", dir.display());
+    }
+    let Some(loader) = loader else {
+        anyhow::bail!("Could not determine the instance loader, set it with --loader");
+    };
+    match game_version {
+        Some(game_version) => eprintln!("[✓] Looking for versions for Minecraft {}", game_version),
+        None => eprintln!("[!] Could not determine the Minecraft version, set it with --game-version"),
+    }
 
-    if archive.by_name("fabric.mod.json").is_ok() {
-        return Ok(vec![parse_fabric_mod_contents(&mut archive, &file_name)?]);
+    let mut missing = Vec::new();
+    for error in analyze_dependencies(mods, Some(loader)).err().map(|e| e.0).unwrap_or_default() {
+        if let DependencyError::MissingDependency(_, _, dependency_id, _) = error
+            && !missing.contains(&dependency_id) {
+            missing.push(dependency_id);
+        }
     }
-    if archive.by_name("META-INF/mods.toml").is_ok() {
-        return parse_forge_mod_contents(&mut archive, &file_name);
+    if missing.is_empty() {
+        println!("No missing dependencies");
+        return Ok(());
     }
 
-    if archive.by_name("META-INF/neoforge.mods.toml").is_ok() {
-        return parse_neoforge_mod_contents(&mut archive, &file_name);
+    let resolutions = modrinth::resolve_missing(client, &missing, loader, game_version)?;
+    for dependency_id in &missing {
+        match resolutions.iter().find(|r| &r.dependency_id == dependency_id) {
+            Some(r) => println!("{}: {} {} ({})", dependency_id, r.project.title, r.version.version_number, r.project.slug),
+            None => println!("{}: no compatible version found on Modrinth", dependency_id),
+        }
+    }
+    if resolutions.is_empty() {
+        return Ok(());
     }
 
-    Err(anyhow::anyhow!(
-        "Unsupported mod file format in '{}'. \
-        Expected one of: fabric.mod.json, META-INF/mods.toml (Forge), or META-INF/neoforge.mods.toml (NeoForge)",
-        file_name
-    ))
+    if !yes {
+        eprint!("Download {} files into {}? [y/N] ", resolutions.len(), dir.display());
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        if !answer.trim().eq_ignore_ascii_case("y") {
+            return Ok(());
+        }
+    }
+    for resolution in &resolutions {
+        let Some(file) = resolution.version.primary_file() else { continue };
+        let path = client.download(file, dir)?;
+        println!("[✓] Downloaded {}", path.display());
+    }
+    Ok(())
 }
 
-fn load_mods_from_dir(dir: &Path) -> Result<Vec<ModMetadata>> {
-    let mut mods = Vec::new();
-
-    for entry in std::fs::read_dir(dir)? {
-        let path = entry?.path();
-
-        if path.is_file() && path.extension().map_or(false, |ext| ext == "jar") {
-            let file_name = path.file_name()
-                .and_then(|f| f.to_str())
-                .unwrap_or("unknown.jar");
+/// The default lockfile of `dir`, which may also be a modpack file.
+fn lockfile_path(dir: &Path) -> PathBuf {
+    let dir = if dir.is_file() { dir.parent().unwrap_or(Path::new(".")) } else { dir };
+    dir.join(lock::LOCK_FILE)
+}
 
-            match parse_mod_file(&path) {
-                Ok(mod_data_vec) => mods.extend(mod_data_vec),
-                Err(e) => eprintln!("Skipping {}: {}", file_name, e),
-            }
+fn apply_toggle(files: &HashMap<String, ModFile>, plan: &toggle::TogglePlan, disable: bool, force: bool) -> Result<()> {
+    if !plan.new_errors.is_empty() {
+        eprintln!("This change introduces:");
+        for error in &plan.new_errors {
+            eprintln!("  {}", error.to_string().replace('\n', "\n  "));
         }
     }
+    if plan.breaks_dependencies() && !force {
+        anyhow::bail!(
+            "Refusing to rename {}: mandatory dependencies would be missing (use --force to rename anyway)",
+            plan.files.join(", ")
+        );
+    }
 
-    Ok(mods)
-}
\ No newline at end of file
+    toggle::apply(files, plan, disable)
+}
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+use serde::Deserialize;
+use crate::r#mod::{DependencyError, ModMetadata, Platform};
+
+/// Mod id of Sinytra Connector, which loads Fabric mods on Forge and NeoForge.
+///
+/// https://github.com/Sinytra/Connector
+pub const CONNECTOR_MOD_ID: &str = "connector";
+
+/// Mod id of Forgified Fabric API, the Forge/NeoForge port of Fabric API used with Connector.
+///
+/// https://github.com/Sinytra/ForgifiedFabricAPI
+pub const FORGIFIED_FABRIC_API_MOD_ID: &str = "fabric_api";
+
+#[derive(Debug, Deserialize)]
+struct IncompatibleMods {
+    mods: Vec<IncompatibleMod>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IncompatibleMod {
+    mod_id: String,
+    reason: String,
+}
+
+/// Platforms whose mods a `loader` instance loads in addition to its own,
+/// either natively or through a bridge mod present in `mods`.
+pub fn bridged_platforms(mods: &[ModMetadata], loader: &Platform) -> Vec<Platform> {
+    match loader {
+        // Quilt loads Fabric mods natively.
+        Platform::Quilt => vec![Platform::Fabric],
+        Platform::Forge | Platform::NeoForge if has_connector(mods, loader) => vec![Platform::Fabric],
+        _ => Vec::new(),
+    }
+}
+
+pub fn has_connector(mods: &[ModMetadata], loader: &Platform) -> bool {
+    matches!(loader, Platform::Forge | Platform::NeoForge)
+        && mods.iter().any(|m| &m.platform == loader && m.mod_id == CONNECTOR_MOD_ID)
+}
+
+/// Maps the Fabric API ids that Fabric mods depend on to the mods that provide
+/// them under Connector.
+///
+/// Forge and NeoForge mod ids can't contain dashes, so Forgified Fabric API
+/// ships its modules with underscores (`fabric-api-base` becomes `fabric_api_base`).
+/// Modules that are not present on their own are bundled in the main
+/// Forgified Fabric API jar.
+pub fn connector_aliases(mods: &[&ModMetadata]) -> HashMap<String, String> {
+    let installed = |mod_id: &str| mods.iter().any(|m| m.mod_id == mod_id);
+    let mut aliases = HashMap::new();
+
+    for mod_ in mods.iter().filter(|m| m.platform == Platform::Fabric) {
+        for dep in &mod_.dependencies {
+            if !is_fabric_api_module(&dep.mod_id) || installed(&dep.mod_id) {
+                continue;
+            }
+
+            let forgified_id = dep.mod_id.replace('-', "_");
+            if installed(&forgified_id) {
+                aliases.insert(dep.mod_id.clone(), forgified_id);
+            } else if installed(FORGIFIED_FABRIC_API_MOD_ID) {
+                aliases.insert(dep.mod_id.clone(), FORGIFIED_FABRIC_API_MOD_ID.to_string());
+            }
+        }
+    }
+
+    aliases
+}
+
+/// Reports the Fabric mods in `mods` that Connector is known not to support.
+pub fn connector_incompatibilities(mods: &[&ModMetadata]) -> Vec<DependencyError> {
+    let incompatible: IncompatibleMods = toml::from_str(include_str!("data/connector_incompatible.toml"))
+        .expect("bundled connector_incompatible.toml is valid");
+
+    mods.iter()
+        .filter(|m| m.platform == Platform::Fabric)
+        .filter_map(|m| {
+            incompatible.mods.iter()
+                .find(|entry| entry.mod_id == m.mod_id)
+                .map(|entry| DependencyError::UnsupportedByBridge(
+                    m.mod_id.clone(),
+                    m.file_name.clone(),
+                    entry.reason.clone(),
+                ))
+        })
+        .collect()
+}
+
+/// Whether `mod_id` is Fabric API or one of its modules, e.g. `fabric-networking-api-v1`.
+fn is_fabric_api_module(mod_id: &str) -> bool {
+    let versioned_module = mod_id.rsplit_once("-v")
+        .is_some_and(|(_, version)| !version.is_empty() && version.chars().all(|c| c.is_ascii_digit()));
+
+    matches!(mod_id, "fabric" | "fabric-api" | "fabric-api-base" | "fabric-renderer-indigo")
+        || (mod_id.starts_with("fabric-") && versioned_module)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connector_aliases() {
+        let mods = [
+            ModMetadata::new("connector", "1.0.0").with_platform(Platform::NeoForge),
+            ModMetadata::new("fabric_api", "1.0.0").with_platform(Platform::NeoForge),
+            ModMetadata::new("fabric_networking_api_v1", "1.0.0").with_platform(Platform::NeoForge),
+            ModMetadata::new("fabric_mod", "1.0.0")
+                .with_platform(Platform::Fabric)
+                .with_dependencies(&[("fabric-api", "*"), ("fabric-networking-api-v1", "*"), ("fabric-rendering-v1", "*"), ("fabric-language-kotlin", "*")]),
+        ];
+        let refs: Vec<_> = mods.iter().collect();
+        let aliases = connector_aliases(&refs);
+
+        assert_eq!(aliases.get("fabric-api").map(String::as_str), Some("fabric_api"));
+        assert_eq!(aliases.get("fabric-networking-api-v1").map(String::as_str), Some("fabric_networking_api_v1"));
+        assert_eq!(aliases.get("fabric-rendering-v1").map(String::as_str), Some("fabric_api"));
+        assert!(!aliases.contains_key("fabric-language-kotlin"));
+    }
+
+    #[test]
+    fn test_connector_incompatibilities() {
+        let mods = [
+            ModMetadata::new("sodium", "1.0.0").with_platform(Platform::Fabric),
+            ModMetadata::new("sodium_neo", "1.0.0").with_platform(Platform::NeoForge),
+        ];
+        let refs: Vec<_> = mods.iter().collect();
+        let errors = connector_incompatibilities(&refs);
+
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(&errors[0], DependencyError::UnsupportedByBridge(mod_id, _, _) if mod_id == "sodium"));
+    }
+}
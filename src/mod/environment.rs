@@ -0,0 +1,92 @@
+use crate::r#mod::{DependencyError, ModMetadata, Platform};
+
+/// The game and loader versions an instance targets, when known, e.g. from a
+/// modpack index.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Environment {
+    pub minecraft: Option<String>,
+    pub loader: Option<(Platform, String)>,
+}
+
+impl Environment {
+    /// The version of the game or loader a dependency on `mod_id` refers to.
+    fn version_of(&self, mod_id: &str, platform: &Platform) -> Option<&str> {
+        if mod_id == "minecraft" {
+            return self.minecraft.as_deref();
+        }
+
+        let (loader, version) = self.loader.as_ref()?;
+        let loader_id = match loader {
+            Platform::Fabric => "fabricloader",
+            Platform::Quilt => "quilt_loader",
+            Platform::Forge => "forge",
+            Platform::NeoForge => "neoforge",
+            Platform::Unknown(_) => return None,
+        };
+        (mod_id == loader_id && platform == loader).then_some(version.as_str())
+    }
+}
+
+/// Checks the mandatory dependencies of `mods` on the game and the loader
+/// against the versions of `environment`.
+///
+/// Ranges or versions this tool can't parse are assumed to match.
+pub fn check_environment(mods: &[ModMetadata], environment: &Environment) -> Vec<DependencyError> {
+    let mut errors = Vec::new();
+
+    for mod_ in mods {
+        for dep in mod_.dependencies.iter().filter(|dep| dep.mandatory) {
+            let Some(target) = environment.version_of(&dep.mod_id, &mod_.platform) else { continue };
+            if !dep.version_range.allows(target) {
+                errors.push(DependencyError::IncompatibleEnvironment(
+                    mod_.mod_id.clone(),
+                    mod_.file_name.clone(),
+                    dep.mod_id.clone(),
+                    dep.version_range.to_string(),
+                    target.to_string(),
+                ));
+            }
+        }
+    }
+
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_environment() {
+        let environment = Environment {
+            minecraft: Some("1.21".to_string()),
+            loader: Some((Platform::Fabric, "0.15.11".to_string())),
+        };
+        let mods = [
+            ModMetadata::new("example", "1.0.0")
+                .with_platform(Platform::Fabric)
+                .with_dependencies(&[("minecraft", "~1.20.1"), ("fabricloader", ">=0.15.0")]),
+            ModMetadata::new("example", "1.0.0")
+                .with_platform(Platform::Fabric)
+                .with_dependencies(&[("minecraft", "1.21.x"), ("fabricloader", ">=0.16.0")]),
+            ModMetadata::new("example", "1.0.0")
+                .with_platform(Platform::Forge)
+                .with_dependencies(&[("minecraft", "[1.21,1.22)"), ("forge", "[99,)")]),
+            ModMetadata::new("example", "1.0.0")
+                .with_platform(Platform::Fabric)
+                .with_dependencies(&[("minecraft", ">=1.20 <1.22")]),
+        ];
+
+        let errors: Vec<_> = check_environment(&mods, &environment)
+            .into_iter()
+            .map(|e| match e {
+                DependencyError::IncompatibleEnvironment(_, _, dep, _, target) => (dep, target),
+                other => panic!("unexpected error: {}", other),
+            })
+            .collect();
+        assert_eq!(errors, vec![
+            ("minecraft".to_string(), "1.21".to_string()),
+            ("fabricloader".to_string(), "0.15.11".to_string()),
+        ]);
+    }
+}
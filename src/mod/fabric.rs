@@ -1,11 +1,9 @@
 use std::collections::HashMap;
-use std::fs::File;
 use serde::Deserialize;
-use serde_json;
-use anyhow::{Context, Result};
-use zip::ZipArchive;
-use std::io::Read;
-use crate::r#mod::{ModDependency, ModMetadata, Platform, DependencyVersionRange};
+use crate::error::{Error, Result};
+use crate::jar::{self, Archive};
+use crate::r#mod::parser::ModParser;
+use crate::r#mod::{ModDependency, ModMetadata, Platform, DependencyVersionRange, Side};
 
 // https://docs.fabricmc.net/develop/getting-started/project-structure#fabric-mod-json
 
@@ -22,6 +20,8 @@ pub struct FabricMod {
     pub schema_version: u32,
     /// The mod's ID, which should be unique.
     pub id: String,
+    /// Other ids the mod can be depended on by, e.g. an old id after a rename.
+    pub provides: Option<Vec<String>>,
     /// The mod's version
     pub version: String,
     /// The mod's name.
@@ -35,6 +35,8 @@ pub struct FabricMod {
     pub environment: Option<String>,
     /// The mods that the mod depends on.
     pub depends: Option<HashMap<String, DependencyVersion>>,
+    /// Custom fields added by other tools, e.g. `modmenu`.
+    pub custom: Option<HashMap<String, serde_json::Value>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -54,26 +56,35 @@ pub struct Contact {
     pub issues: Option<String>,
 }
 
-pub fn parse_fabric_mod_contents(jar_file: &mut ZipArchive<File>, file_name: &String) -> Result<ModMetadata> {
-    let mut file = jar_file.by_name("fabric.mod.json")?;
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
-    let json: FabricMod = serde_json::from_str(contents.as_str())
-        .with_context(|| format!("Failed to parse Fabric fabric.mod.json from {}", file_name))?;
+const METADATA_FILE: &str = "fabric.mod.json";
 
-    let mut metadata = ModMetadata::try_from(&json)
-        .with_context(|| format!("Failed to convert Fabric fabric.mod.json to metadata for {}", file_name))?;
-    
-    metadata.file_name = file_name.clone();
+/// Reads the `fabric.mod.json` of Fabric mods.
+pub struct FabricParser;
 
-    Ok(metadata)
-}
+impl ModParser for FabricParser {
+    fn name(&self) -> &str {
+        METADATA_FILE
+    }
+
+    fn detect(&self, archive: &mut dyn Archive) -> bool {
+        archive.contains(METADATA_FILE)
+    }
 
-impl TryFrom<&FabricMod> for ModMetadata {
-    type Error = anyhow::Error;
+    fn parse(&self, archive: &mut dyn Archive, file_name: &str) -> Result<Vec<ModMetadata>> {
+        let contents = jar::read_entry(archive, METADATA_FILE, file_name)?;
+        let json: FabricMod = serde_json::from_str(contents.as_str())
+            .map_err(|e| Error::metadata(file_name, METADATA_FILE, e))?;
 
-    fn try_from(json: &FabricMod) -> Result<Self, Self::Error> {
-        Ok(ModMetadata {
+        let mut metadata = ModMetadata::from(&json);
+        metadata.file_name = file_name.to_string();
+
+        Ok(vec![metadata])
+    }
+}
+
+impl From<&FabricMod> for ModMetadata {
+    fn from(json: &FabricMod) -> Self {
+        ModMetadata {
             mod_id: json.id.clone(),
             version: json.version.clone(),
             name: json.name.clone(),
@@ -82,7 +93,10 @@ impl TryFrom<&FabricMod> for ModMetadata {
             platform: Platform::Fabric,
             dependencies: parse_fabric_dependencies(json),
             file_name: "".to_string(),
-        })
+            library: is_library(json),
+            side: parse_side(&json.environment),
+            provides: json.provides.clone().unwrap_or_default(),
+        }
     }
 }
 
@@ -94,6 +108,25 @@ fn parse_authors(authors: &Option<Vec<Author>>) -> Vec<String> {
     }).collect()
 }
 
+fn parse_side(environment: &Option<String>) -> Side {
+    match environment.as_deref() {
+        Some("client") => Side::Client,
+        Some("server") => Side::Server,
+        _ => Side::Both,
+    }
+}
+
+/// Whether Mod Menu's `custom.modmenu.badges` marks the mod as a library.
+///
+/// https://github.com/TerraformersMC/ModMenu#fabric-metadata-api
+fn is_library(json: &FabricMod) -> bool {
+    json.custom.as_ref()
+        .and_then(|custom| custom.get("modmenu"))
+        .and_then(|modmenu| modmenu.get("badges"))
+        .and_then(|badges| badges.as_array())
+        .is_some_and(|badges| badges.iter().any(|badge| badge == "library"))
+}
+
 fn parse_fabric_dependencies(json: &FabricMod) -> Vec<ModDependency> {
     let mut deps = Vec::new();
 
@@ -145,12 +178,9 @@ mod tests {
         }"#;
         let file_name = "fabric.mod.json".to_string();
         let json: FabricMod = serde_json::from_str(json_content)
-            .with_context(|| format!("Failed to parse Fabric fabric.mod.json from {}", file_name))
             .unwrap();
 
-        let mut metadata = ModMetadata::try_from(&json)
-            .with_context(|| format!("Failed to convert Fabric fabric.mod.json to metadata for {}", file_name))
-            .unwrap();
+        let mut metadata = ModMetadata::from(&json);
 
         metadata.file_name = file_name.clone();
 
@@ -173,12 +203,9 @@ mod tests {
         }"#;
         let file_name = "fabric.mod.json".to_string();
         let json: FabricMod = serde_json::from_str(json_content)
-            .with_context(|| format!("Failed to parse Fabric fabric.mod.json from {}", file_name))
             .unwrap();
 
-        let mut metadata = ModMetadata::try_from(&json)
-            .with_context(|| format!("Failed to convert Fabric fabric.mod.json to metadata for {}", file_name))
-            .unwrap();
+        let mut metadata = ModMetadata::from(&json);
 
         metadata.file_name = file_name.clone();
 
@@ -191,4 +218,22 @@ mod tests {
             _ => panic!("Expected Multiple variant"),
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parse_fabric_library_badge() {
+        let json_content = r#"{
+            "schemaVersion": 1,
+            "id": "my_lib",
+            "version": "1.0.0",
+            "custom": {
+                "modmenu": {
+                    "badges": ["library"]
+                }
+            }
+        }"#;
+        let json: FabricMod = serde_json::from_str(json_content).unwrap();
+        let metadata = ModMetadata::from(&json);
+
+        assert!(metadata.library);
+    }
+}
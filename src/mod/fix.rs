@@ -0,0 +1,363 @@
+use std::fmt;
+use std::sync::LazyLock;
+use semver::Version;
+use serde::Deserialize;
+use crate::r#mod::version::{intersect_intervals, normalize_version, Interval};
+use crate::r#mod::{compare_versions, dependency_graph, DependencyError, DependencyGraph, ModMetadata, Platform};
+
+/// What to do about the version conflicts over one mod.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fix {
+    /// Switching `mod_id` from `installed` to a version in `range` satisfies
+    /// every mod depending on it. `jars` are the candidates in that range,
+    /// when a candidate folder is configured.
+    ChangeVersion {
+        mod_id: String,
+        installed: String,
+        direction: Direction,
+        range: String,
+        jars: Option<Vec<String>>,
+    },
+    /// No version of `mod_id` satisfies all of these dependents, as `(mod_id, range)` pairs.
+    Incompatible {
+        mod_id: String,
+        dependents: Vec<(String, String)>,
+    },
+}
+
+/// The installed mod a missing dependency is likely to be under another id.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    pub mod_id: String,
+    pub file_name: String,
+    pub likeness: Likeness,
+}
+
+/// Why an installed mod is taken for a missing dependency, from most to least certain.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Likeness {
+    /// A known rename, with a note about it.
+    Renamed(String),
+    /// The ids only differ in case, `-` or `_`.
+    Spelling,
+    /// The mod provides an id that is spelled alike.
+    Provides(String),
+    /// The mod's name is spelled like the missing id.
+    Name(String),
+    /// The ids are spelled alike.
+    SimilarId,
+}
+
+#[derive(Debug, Deserialize)]
+struct Renames {
+    renames: Vec<Rename>,
+}
+
+static RENAMES: LazyLock<Renames> = LazyLock::new(|| {
+    toml::from_str(include_str!("data/renamed_ids.toml")).expect("bundled renamed_ids.toml is valid")
+});
+
+#[derive(Debug, Deserialize)]
+struct Rename {
+    from: String,
+    to: String,
+    note: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Upgrade,
+    Downgrade,
+    /// Somewhere between older and newer versions that don't fit.
+    Change,
+}
+
+impl fmt::Display for Fix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Fix::ChangeVersion { mod_id, installed, direction, range, jars } => {
+                match direction {
+                    Direction::Upgrade => write!(f, "Upgrade {} from {} to", mod_id, installed)?,
+                    Direction::Downgrade => write!(f, "Downgrade {} from {} to", mod_id, installed)?,
+                    Direction::Change => write!(f, "Replace {} {} with", mod_id, installed)?,
+                }
+                write!(f, " a version matching {}", range)?;
+                match jars.as_deref() {
+                    Some([]) => write!(f, ", none of the candidate jars does"),
+                    Some(jars) => write!(f, ", e.g. {}", jars.join(", ")),
+                    None => Ok(()),
+                }
+            }
+            Fix::Incompatible { mod_id, dependents } => {
+                let dependents: Vec<String> = dependents.iter()
+                    .map(|(dependent, range)| format!("{} (needs {})", dependent, range))
+                    .collect();
+                write!(
+                    f,
+                    "No version of {} satisfies {}, update or remove one of them",
+                    mod_id, dependents.join(" and ")
+                )
+            }
+        }
+    }
+}
+
+impl fmt::Display for Suggestion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "did you mean {} ({})? ", self.mod_id, self.file_name)?;
+        match &self.likeness {
+            Likeness::Renamed(note) => write!(f, "{}", note),
+            Likeness::Spelling => write!(f, "The ids only differ in spelling"),
+            Likeness::Provides(id) => write!(f, "It provides {}", id),
+            Likeness::Name(name) => write!(f, "It is named {}", name),
+            Likeness::SimilarId => write!(f, "The ids are alike"),
+        }
+    }
+}
+
+/// Suggests a fix for each mod `errors` report version conflicts over,
+/// naming the jars of `candidates` that would do when given.
+///
+/// Dependency ids resolve to mods like in the dependency graph of a `loader`
+/// instance, through the ids mods provide and the aliases of bridges.
+pub fn suggest_fixes(
+    mods: &[ModMetadata],
+    errors: &[DependencyError],
+    loader: Option<&Platform>,
+    candidates: Option<&[ModMetadata]>,
+) -> Vec<Fix> {
+    let graph = dependency_graph(mods, loader);
+    let mut seen: Vec<&str> = Vec::new();
+    let mut fixes = Vec::new();
+    for error in errors {
+        if let DependencyError::VersionConflict(_, dependency_id, ..) = error
+            && let Some(installed) = graph.get(dependency_id)
+            && !seen.contains(&installed.mod_id.as_str()) {
+            seen.push(&installed.mod_id);
+            fixes.extend(suggest_version(&graph, installed, candidates));
+        }
+    }
+    fixes
+}
+
+/// Ids as written by hand tend to differ in these.
+fn normalize_id(id: &str) -> String {
+    id.chars()
+        .filter(|c| !matches!(c, '-' | '_' | ' '))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// How alike two normalized ids or names need to be to suggest one for the other.
+const SIMILARITY_THRESHOLD: f64 = 0.8;
+
+/// The mod of `mods` the missing `dependency_id` most likely is.
+pub(crate) fn suggest_mod<'a>(mods: impl IntoIterator<Item = &'a ModMetadata>, dependency_id: &str) -> Option<Suggestion> {
+    let normalized = normalize_id(dependency_id);
+    let similarity = |other: &str| strsim::normalized_levenshtein(&normalized, &normalize_id(other));
+
+    // Ranked by likeness first, then by how alike the spelling is.
+    let mut best: Option<(&ModMetadata, Likeness, (u8, f64))> = None;
+    for mod_ in mods {
+        let likeness = if let Some(rename) = RENAMES.renames.iter().find(|r| r.from == dependency_id && r.to == mod_.mod_id) {
+            Some((Likeness::Renamed(rename.note.clone()), (0, 1.0)))
+        } else if normalize_id(&mod_.mod_id) == normalized {
+            Some((Likeness::Spelling, (1, 1.0)))
+        } else if let Some((id, score)) = mod_.provides.iter()
+            .map(|id| (id, similarity(id)))
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .filter(|(_, score)| *score >= SIMILARITY_THRESHOLD) {
+            Some((Likeness::Provides(id.clone()), (2, score)))
+        } else if let Some(name) = &mod_.name
+            && similarity(name) >= SIMILARITY_THRESHOLD {
+            Some((Likeness::Name(name.clone()), (3, similarity(name))))
+        } else {
+            let score = similarity(&mod_.mod_id);
+            (score >= SIMILARITY_THRESHOLD).then_some((Likeness::SimilarId, (4, score)))
+        };
+
+        if let Some((likeness, rank)) = likeness
+            && best.as_ref().is_none_or(|(_, _, best)| rank.0 < best.0 || (rank.0 == best.0 && rank.1 > best.1)) {
+            best = Some((mod_, likeness, rank));
+        }
+    }
+
+    let (mod_, likeness, _) = best?;
+    Some(Suggestion {
+        mod_id: mod_.mod_id.clone(),
+        file_name: mod_.file_name.clone(),
+        likeness,
+    })
+}
+
+fn suggest_version(graph: &DependencyGraph, installed: &ModMetadata, candidates: Option<&[ModMetadata]>) -> Option<Fix> {
+    let mod_id = installed.mod_id.as_str();
+    let edges = graph.dependents(installed);
+    // Ranges this tool can't parse are reported as invalid on their own.
+    let dependents: Vec<(&ModMetadata, String, Vec<Interval>)> = edges.iter()
+        .filter_map(|edge| {
+            let range = &edge.dependency.version_range;
+            Some((edge.from, range.to_string(), range.intervals()?))
+        })
+        .collect();
+    let allowed = |indices: &[usize]| indices.iter()
+        .fold(vec![Interval::any()], |allowed, &i| intersect_intervals(&allowed, &dependents[i].2));
+
+    let all: Vec<usize> = (0..dependents.len()).collect();
+    let range = allowed(&all);
+    if range.is_empty() {
+        // Narrow down to dependents that conflict even without the others.
+        let mut core = all;
+        let mut i = 0;
+        while i < core.len() {
+            let mut without = core.clone();
+            without.remove(i);
+            if allowed(&without).is_empty() {
+                core = without;
+            } else {
+                i += 1;
+            }
+        }
+        return Some(Fix::Incompatible {
+            mod_id: mod_id.to_string(),
+            dependents: core.into_iter()
+                .map(|i| (dependents[i].0.mod_id.clone(), dependents[i].1.clone()))
+                .collect(),
+        });
+    }
+
+    let direction = match Version::parse(&normalize_version(&installed.version)) {
+        Ok(version) if range.iter().all(|interval| interval.is_above(&version)) => Direction::Upgrade,
+        Ok(version) if range.iter().all(|interval| interval.is_below(&version)) => Direction::Downgrade,
+        _ => Direction::Change,
+    };
+    let jars = candidates.map(|candidates| {
+        let mut matching: Vec<&ModMetadata> = candidates.iter()
+            .filter(|candidate| candidate.mod_id == mod_id || edges.iter()
+                .any(|edge| candidate.provides.contains(&edge.dependency.mod_id)))
+            .filter(|candidate| Version::parse(&normalize_version(&candidate.version))
+                .is_ok_and(|version| range.iter().any(|interval| interval.contains(&version))))
+            .collect();
+        matching.sort_by(|a, b| compare_versions(&b.version, &a.version));
+        matching.into_iter().map(|candidate| candidate.file_name.clone()).collect()
+    });
+    let range: Vec<String> = range.iter().map(|interval| interval.to_string()).collect();
+
+    Some(Fix::ChangeVersion {
+        mod_id: mod_id.to_string(),
+        installed: installed.version.clone(),
+        direction,
+        range: range.join(" || "),
+        jars,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r#mod::analyze_dependencies;
+
+    fn fixes(mods: &[ModMetadata], candidates: Option<&[ModMetadata]>) -> Vec<Fix> {
+        let errors = analyze_dependencies(mods, Some(&Platform::Fabric)).unwrap_err().0;
+        suggest_fixes(mods, &errors, Some(&Platform::Fabric), candidates)
+    }
+
+    #[test]
+    fn test_suggests_a_range_satisfying_every_dependent() {
+        let mods = vec![
+            ModMetadata::new("lib", "1.0.0"),
+            ModMetadata::new("a", "1.0.0").with_dependencies(&[("lib", ">=1.2.0")]),
+            ModMetadata::new("b", "1.0.0").with_dependencies(&[("lib", "<2.0.0")]),
+        ];
+        let candidates = vec![ModMetadata::new("lib", "1.5.0"), ModMetadata::new("lib", "2.1.0"), ModMetadata::new("lib", "1.3.0")];
+        assert_eq!(fixes(&mods, Some(&candidates)), vec![Fix::ChangeVersion {
+            mod_id: "lib".to_string(),
+            installed: "1.0.0".to_string(),
+            direction: Direction::Upgrade,
+            range: ">=1.2.0, <2.0.0".to_string(),
+            jars: Some(vec!["lib-1.5.0.jar".to_string(), "lib-1.3.0.jar".to_string()]),
+        }]);
+    }
+
+    #[test]
+    fn test_resolves_provided_ids_like_the_graph() {
+        let mods = vec![
+            ModMetadata::new("fabric-api", "0.80.0").with_provides(&["fabric"]),
+            ModMetadata::new("a", "1.0.0").with_dependencies(&[("fabric", ">=0.90.0")]),
+            ModMetadata::new("b", "1.0.0").with_dependencies(&[("fabric-api", "<1.0.0")]),
+        ];
+        let candidates = vec![
+            ModMetadata::new("fabric-api", "0.92.0").with_provides(&["fabric"]),
+            ModMetadata::new("fabric-api", "1.0.0").with_provides(&["fabric"]),
+        ];
+        assert_eq!(fixes(&mods, Some(&candidates)), vec![Fix::ChangeVersion {
+            mod_id: "fabric-api".to_string(),
+            installed: "0.80.0".to_string(),
+            direction: Direction::Upgrade,
+            range: ">=0.90.0, <1.0.0".to_string(),
+            jars: Some(vec!["fabric-api-0.92.0.jar".to_string()]),
+        }]);
+    }
+
+    #[test]
+    fn test_names_the_dependents_that_exclude_each_other() {
+        let mods = vec![
+            ModMetadata::new("lib", "3.0.0"),
+            ModMetadata::new("a", "1.0.0").with_dependencies(&[("lib", ">=2.0.0")]),
+            ModMetadata::new("b", "1.0.0").with_dependencies(&[("lib", "^1.0.0")]),
+            ModMetadata::new("c", "1.0.0").with_dependencies(&[("lib", "<3.0.0")]),
+        ];
+        // Both `b` and `c` conflict, even though `lib` is resolved by then.
+        let errors = analyze_dependencies(&mods, Some(&Platform::Fabric)).unwrap_err().0;
+        let conflicts = errors.iter().filter(|e| matches!(e, DependencyError::VersionConflict(..))).count();
+        assert_eq!(conflicts, 2);
+
+        let fixes = fixes(&mods, None);
+        assert_eq!(fixes, vec![Fix::Incompatible {
+            mod_id: "lib".to_string(),
+            dependents: vec![("a".to_string(), ">=2.0.0".to_string()), ("b".to_string(), "^1.0.0".to_string())],
+        }]);
+        assert_eq!(
+            fixes[0].to_string(),
+            "No version of lib satisfies a (needs >=2.0.0) and b (needs ^1.0.0), update or remove one of them"
+        );
+    }
+
+    #[test]
+    fn test_suggests_the_installed_mod_a_missing_dependency_likely_is() {
+        let did_you_mean = |mods: &[ModMetadata]| {
+            match analyze_dependencies(mods, Some(&Platform::Fabric)).unwrap_err().0.as_slice() {
+                [DependencyError::MissingDependency(.., suggestion)] => {
+                    suggestion.as_ref().map(|s| (s.mod_id.clone(), s.likeness.clone()))
+                }
+                errors => panic!("unexpected errors {:?}", errors),
+            }
+        };
+
+        let mods = vec![ModMetadata::new("app", "1.0.0").with_dependencies(&[("fabric", "*")]), ModMetadata::new("fabric-api", "0.92.0")];
+        assert!(matches!(did_you_mean(&mods), Some((id, Likeness::Renamed(_))) if id == "fabric-api"));
+
+        let mods = vec![ModMetadata::new("app", "1.0.0").with_dependencies(&[("cloth_config", "*")]), ModMetadata::new("cloth-config", "11.1.0")];
+        assert_eq!(did_you_mean(&mods), Some(("cloth-config".to_string(), Likeness::Spelling)));
+        assert_eq!(
+            analyze_dependencies(&mods, Some(&Platform::Fabric)).unwrap_err().to_string(),
+            "Missing dependency for app (app-1.0.0.jar): cloth_config, \
+             did you mean cloth-config (cloth-config-11.1.0.jar)? The ids only differ in spelling",
+        );
+
+        let provider = ModMetadata::new("magnesium_extras", "1.0.0").with_provides(&["rubidium_extra"]);
+        let mods = vec![ModMetadata::new("app", "1.0.0").with_dependencies(&[("rubidium-extras", "*")]), ModMetadata::new("other", "1.0.0"), provider];
+        assert_eq!(did_you_mean(&mods), Some(("magnesium_extras".to_string(), Likeness::Provides("rubidium_extra".to_string()))));
+
+        let mut named = ModMetadata::new("yacl", "3.0.0");
+        named.name = Some("YetAnotherConfigLib".to_string());
+        let mods = vec![ModMetadata::new("app", "1.0.0").with_dependencies(&[("yet_another_config_lib", "*")]), named];
+        assert_eq!(did_you_mean(&mods), Some(("yacl".to_string(), Likeness::Name("YetAnotherConfigLib".to_string()))));
+
+        let mods = vec![ModMetadata::new("app", "1.0.0").with_dependencies(&[("archtectury", "*")]), ModMetadata::new("architectury", "9.1.0")];
+        assert_eq!(did_you_mean(&mods), Some(("architectury".to_string(), Likeness::SimilarId)));
+
+        let mods = vec![ModMetadata::new("app", "1.0.0").with_dependencies(&[("jei", "*")]), ModMetadata::new("rei", "1.0.0")];
+        assert_eq!(did_you_mean(&mods), None);
+    }
+}
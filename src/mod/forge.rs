@@ -1,9 +1,8 @@
-use std::fs::File;
 use serde::Deserialize;
-use anyhow::{Context, Result};
-use zip::ZipArchive;
-use std::io::Read;
-use crate::r#mod::{DependencyVersionRange, ModDependency, ModMetadata, Platform};
+use crate::error::{Error, Result};
+use crate::jar::{self, Archive};
+use crate::r#mod::parser::ModParser;
+use crate::r#mod::{DependencyVersionRange, ModDependency, ModMetadata, Platform, Side};
 
 // https://docs.minecraftforge.net/en/latest/gettingstarted/modfiles/#modstoml
 #[derive(Debug, Deserialize)]
@@ -16,24 +15,15 @@ pub struct ForgeMod {
     /// Forge provides the Java loader `javafml`
     /// and low/no code loader `lowcodefml`.
     #[serde(rename = "modLoader")]
+    #[allow(dead_code)]
     pub mod_loader: String,
     /// The acceptable version range of the language loader,
     /// expressed as a Maven Version Range.
     /// For `javafml` and `lowcodefml`,
     /// the version is the major version of the Forge version.
     #[serde(rename = "loaderVersion")]
+    #[allow(dead_code)]
     pub loader_version: String,
-    /// The license the mod(s) in this JAR are provided under.
-    #[serde(rename = "license")]
-    pub license: Option<String>,
-    /// A URL representing the place to report and track issues with the mod(s).
-    #[serde(rename = "issueTrackerURL")]
-    pub issue_tracker_url: Option<String>,
-    /// When `true`, the mod(s)’s resources will be displayed as
-    /// a separate resource pack on the ‘Resource Packs’ menu,
-    /// rather than being combined with the `Mod resources` pack.
-    #[serde(rename = "showAsResourcePack")]
-    pub show_as_resource_pack: Option<bool>,
     /// Weather the mod is only needed on client side or not.
     #[serde(rename = "clientSideOnly")]
     pub client_side_only: Option<bool>,
@@ -54,9 +44,6 @@ pub struct ModEntry {
     /// The unique identifier representing this mod.
     #[serde(rename = "modId")]
     pub mod_id: String,
-    /// An override namespace for the mod.
-    #[serde(rename = "namespace")]
-    pub namespace: Option<String>,
     /// The version of the mod, preferably in a variation of Maven versioning.
     /// When set to `${file.jarVersion}`, it will be replaced with the value of the
     /// `Implementation-Version` property in the JAR’s manifest
@@ -70,30 +57,9 @@ pub struct ModEntry {
     /// The description of the mod shown in the mod list screen.
     #[serde(rename = "description")]
     pub description: Option<String>,
-    /// The name and extension of an image file used on the mods list screen.
-    /// The logo must be in the root of the JAR or directly in the root of the source set
-    /// (e.g., `src/main/resources` for the main source set).
-    #[serde(rename = "logoFile")]
-    pub logo_file: Option<String>,
-    /// Whether to use `GL_LINEAR*` (true) or `GL_NEAREST*` (false) to render the logoFile.
-    #[serde(rename = "logoBlur")]
-    pub logo_blur: Option<bool>,
-    /// A URL to a JSON used by the [update checker](https://docs.minecraftforge.net/en/latest/misc/updatechecker/)
-    /// to make sure the mod you are playing is the latest version.
-    #[serde(rename = "updateJSONURL")]
-    pub update_json_url: Option<String>,
-    /// Credits and acknowledges for the mod shown on the mod list screen.
-    #[serde(rename = "credits")]
-    pub credits: Option<String>,
     /// The authors of the mod shown on the mod list screen.
     #[serde(rename = "authors")]
     pub authors: Option<Authors>,
-    /// A URL to the display page of the mod shown on the mod list screen.
-    #[serde(rename = "displayURL")]
-    pub display_url: Option<String>,
-    /// See [sides](https://docs.minecraftforge.net/en/latest/concepts/sides/#writing-one-sided-mods).
-    #[serde(rename = "displayTest")]
-    pub display_test: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -124,31 +90,51 @@ pub struct DependencyEntry {
     pub version_range: String,
     /// Defines if the mod must load before ("BEFORE") or after ("AFTER") this dependency.
     /// If the ordering does not matter, return "NONE"
+    #[allow(dead_code)]
     pub ordering: String,
     /// The physical side the dependency must be present on: "CLIENT", "SERVER", or "BOTH".
+    #[allow(dead_code)]
     pub side: String,
 }
 
-pub fn parse_forge_mod_contents(jar_file: &mut ZipArchive<File>, file_name: &String) -> Result<Vec<ModMetadata>> {
-    let mut file = jar_file.by_name("META-INF/mods.toml")?;
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
-    drop(file);
+const METADATA_FILE: &str = "META-INF/mods.toml";
+
+/// Reads the `META-INF/mods.toml` of Forge mods.
+pub struct ForgeParser;
+
+impl ModParser for ForgeParser {
+    fn name(&self) -> &str {
+        "META-INF/mods.toml (Forge)"
+    }
+
+    fn detect(&self, archive: &mut dyn Archive) -> bool {
+        archive.contains(METADATA_FILE)
+    }
+
+    fn parse(&self, archive: &mut dyn Archive, file_name: &str) -> Result<Vec<ModMetadata>> {
+        parse_forge_mod_contents(archive, file_name)
+    }
+}
+
+fn parse_forge_mod_contents(jar_file: &mut dyn Archive, file_name: &str) -> Result<Vec<ModMetadata>> {
+    let contents = jar::read_entry(jar_file, METADATA_FILE, file_name)?;
     let toml: ForgeMod = toml::from_str(contents.as_str())
-        .with_context(|| format!("Failed to parse Forge mods.toml from {}", file_name))?;
+        .map_err(|e| Error::metadata(file_name, METADATA_FILE, e))?;
+
+    let library = matches!(
+        jar::manifest_attribute(jar_file, "FMLModType").as_deref(),
+        Some("LIBRARY" | "GAMELIBRARY")
+    );
 
     let mut all_metadata = Vec::new();
 
     for mod_entry in &toml.mods {
         let version = if mod_entry.version == "${file.jarVersion}" {
-            let mut manifest_file = jar_file.by_name("META-INF/MANIFEST.MF")
-                .with_context(|| "META-INF/MANIFEST.MF not found in JAR")?;
-            let mut manifest_contents = String::new();
-            manifest_file.read_to_string(&mut manifest_contents)?;
+            let manifest_contents = jar::read_entry(jar_file, "META-INF/MANIFEST.MF", file_name)?;
 
             let version_line = manifest_contents.lines()
                 .find(|line| line.starts_with("Implementation-Version:"))
-                .with_context(|| "Implementation-Version not found in MANIFEST.MF")?;
+                .ok_or_else(|| Error::metadata(file_name, "META-INF/MANIFEST.MF", "Implementation-Version not found"))?;
             version_line.split(": ").nth(1).unwrap_or("unknown").to_string()
         } else {
             mod_entry.version.clone()
@@ -162,7 +148,10 @@ pub fn parse_forge_mod_contents(jar_file: &mut ZipArchive<File>, file_name: &Str
             authors: parse_authors(&mod_entry.authors),
             platform: Platform::Forge,
             dependencies: parse_forge_dependencies(&toml),
-            file_name: file_name.clone(),
+            file_name: file_name.to_string(),
+            library,
+            side: if toml.client_side_only == Some(true) { Side::Client } else { Side::Both },
+            provides: Vec::new(),
         };
         all_metadata.push(metadata);
     }
@@ -241,7 +230,6 @@ clientSideOnly=false
 "#;
         let file_name = "test.toml".to_string();
         let toml: ForgeMod = toml::from_str(toml_content)
-            .with_context(|| format!("Failed to parse Forge mods.toml from {}", file_name))
             .unwrap();
 
         let mut all_metadata = Vec::new();
@@ -256,6 +244,9 @@ clientSideOnly=false
                 platform: Platform::Forge,
                 dependencies: parse_forge_dependencies(&toml),
                 file_name: file_name.clone(),
+                library: false,
+                side: Side::Both,
+                provides: Vec::new(),
             };
             all_metadata.push(metadata);
         }
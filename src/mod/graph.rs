@@ -0,0 +1,275 @@
+use std::collections::{HashMap, HashSet};
+use crate::r#mod::{is_platform_dependency, ModDependency, ModMetadata};
+use crate::r#mod::version::compare_versions;
+
+/// A dependency between two mods that are both part of the graph.
+#[derive(Debug, Clone, Copy)]
+pub struct Edge<'a> {
+    pub from: &'a ModMetadata,
+    pub to: &'a ModMetadata,
+    pub dependency: &'a ModDependency,
+}
+
+/// The dependency graph of a set of mods, keyed by `mod_id`.
+///
+/// Dependencies on the game or the loader itself (see `is_platform_dependency`)
+/// and dependencies on mods that are not part of the set have no edge.
+/// Aliases, including the ids mods `provides`, let a dependency id resolve
+/// to a mod with another id.
+pub struct DependencyGraph<'a> {
+    nodes: Vec<&'a ModMetadata>,
+    index: HashMap<&'a str, &'a ModMetadata>,
+    aliases: HashMap<String, String>,
+}
+
+impl<'a> DependencyGraph<'a> {
+    pub fn new(mods: &[&'a ModMetadata]) -> Self {
+        let mut nodes = Vec::new();
+        let mut index = HashMap::new();
+        for mod_ in mods {
+            match index.get(mod_.mod_id.as_str()) {
+                None => {
+                    index.insert(mod_.mod_id.as_str(), *mod_);
+                    nodes.push(*mod_);
+                }
+                // Duplicate ids are reported separately; resolve against the newest jar.
+                Some(existing) if compare_versions(&mod_.version, &existing.version).is_gt() => {
+                    let position = nodes.iter().position(|m| m.mod_id == mod_.mod_id).unwrap();
+                    nodes[position] = *mod_;
+                    index.insert(mod_.mod_id.as_str(), *mod_);
+                }
+                Some(_) => {}
+            }
+        }
+
+        let aliases = nodes.iter()
+            .flat_map(|mod_| mod_.provides.iter().map(|id| (id.clone(), mod_.mod_id.clone())))
+            .filter(|(id, _)| !index.contains_key(id.as_str()))
+            .collect();
+        DependencyGraph { nodes, index, aliases }
+    }
+
+    /// Resolves each key of `aliases` to the mod whose id is the value.
+    pub fn with_aliases(mut self, aliases: HashMap<String, String>) -> Self {
+        self.aliases.extend(aliases);
+        self
+    }
+
+    pub fn get(&self, mod_id: &str) -> Option<&'a ModMetadata> {
+        self.index.get(mod_id)
+            .or_else(|| self.aliases.get(mod_id).and_then(|target| self.index.get(target.as_str())))
+            .copied()
+    }
+
+    pub fn nodes(&self) -> &[&'a ModMetadata] {
+        &self.nodes
+    }
+
+    /// Outgoing edges of `mod_`, in declaration order.
+    pub fn edges(&self, mod_: &'a ModMetadata) -> impl Iterator<Item = Edge<'a>> + '_ {
+        mod_.dependencies
+            .iter()
+            .filter(|dep| !is_platform_dependency(&dep.mod_id))
+            .filter_map(move |dep| {
+                self.get(&dep.mod_id).map(|to| Edge { from: mod_, to, dependency: dep })
+            })
+    }
+
+    /// Incoming edges of `mod_`, i.e. the dependencies other mods declare on it.
+    pub fn dependents(&self, mod_: &ModMetadata) -> Vec<Edge<'a>> {
+        self.nodes
+            .iter()
+            .flat_map(|node| self.edges(node))
+            .filter(|edge| edge.to.mod_id == mod_.mod_id)
+            .collect()
+    }
+
+    /// Every chain of edges leading from a top-level mod (one nothing depends
+    /// on) down to `target`, ordered from the top-level mod to `target`.
+    ///
+    /// A chain that runs into a cycle stops at the last mod not yet on it,
+    /// which then still has dependents.
+    pub fn paths_to(&self, target: &'a ModMetadata) -> Vec<Vec<Edge<'a>>> {
+        let mut paths = Vec::new();
+        self.collect_paths(target, &mut vec![target.mod_id.as_str()], &mut Vec::new(), &mut paths);
+        paths
+    }
+
+    fn collect_paths(
+        &self,
+        node: &'a ModMetadata,
+        visited: &mut Vec<&'a str>,
+        chain: &mut Vec<Edge<'a>>,
+        paths: &mut Vec<Vec<Edge<'a>>>,
+    ) {
+        let dependents: Vec<_> = self.dependents(node)
+            .into_iter()
+            .filter(|edge| !visited.contains(&edge.from.mod_id.as_str()))
+            .collect();
+
+        if dependents.is_empty() {
+            if !chain.is_empty() {
+                paths.push(chain.iter().rev().copied().collect());
+            }
+            return;
+        }
+
+        for edge in dependents {
+            visited.push(edge.from.mod_id.as_str());
+            chain.push(edge);
+            self.collect_paths(edge.from, visited, chain, paths);
+            chain.pop();
+            visited.pop();
+        }
+    }
+
+    /// Finds every dependency cycle made of mandatory edges.
+    ///
+    /// Each strongly connected component with more than one member (or a mod
+    /// that requires itself) is reported once, with its members sorted by id.
+    /// Optional edges are ignored, as loaders tolerate mods that optionally
+    /// depend on each other.
+    pub fn cycles(&self) -> Vec<Vec<String>> {
+        let mut tarjan = Tarjan {
+            graph: self,
+            next_index: 0,
+            indices: HashMap::new(),
+            low_links: HashMap::new(),
+            stack: Vec::new(),
+            on_stack: HashSet::new(),
+            components: Vec::new(),
+        };
+
+        for node in &self.nodes {
+            if !tarjan.indices.contains_key(node.mod_id.as_str()) {
+                tarjan.visit(node);
+            }
+        }
+
+        let mut cycles: Vec<Vec<String>> = tarjan.components
+            .into_iter()
+            .filter(|component| {
+                component.len() > 1 || self.edges(component[0])
+                    .any(|edge| edge.dependency.mandatory && edge.to.mod_id == component[0].mod_id)
+            })
+            .map(|component| {
+                let mut members: Vec<String> = component.iter().map(|m| m.mod_id.clone()).collect();
+                members.sort();
+                members
+            })
+            .collect();
+        cycles.sort();
+        cycles
+    }
+}
+
+// https://en.wikipedia.org/wiki/Tarjan%27s_strongly_connected_components_algorithm
+struct Tarjan<'g, 'a> {
+    graph: &'g DependencyGraph<'a>,
+    next_index: usize,
+    indices: HashMap<&'a str, usize>,
+    low_links: HashMap<&'a str, usize>,
+    stack: Vec<&'a ModMetadata>,
+    on_stack: HashSet<&'a str>,
+    components: Vec<Vec<&'a ModMetadata>>,
+}
+
+impl<'a> Tarjan<'_, 'a> {
+    fn visit(&mut self, node: &'a ModMetadata) {
+        let id = node.mod_id.as_str();
+        self.indices.insert(id, self.next_index);
+        self.low_links.insert(id, self.next_index);
+        self.next_index += 1;
+        self.stack.push(node);
+        self.on_stack.insert(id);
+
+        let successors: Vec<_> = self.graph.edges(node)
+            .filter(|edge| edge.dependency.mandatory)
+            .map(|edge| edge.to)
+            .collect();
+
+        for successor in successors {
+            let successor_id = successor.mod_id.as_str();
+            if !self.indices.contains_key(successor_id) {
+                self.visit(successor);
+                let low = self.low_links[id].min(self.low_links[successor_id]);
+                self.low_links.insert(id, low);
+            } else if self.on_stack.contains(successor_id) {
+                let low = self.low_links[id].min(self.indices[successor_id]);
+                self.low_links.insert(id, low);
+            }
+        }
+
+        if self.low_links[id] == self.indices[id] {
+            let mut component = Vec::new();
+            while let Some(member) = self.stack.pop() {
+                self.on_stack.remove(member.mod_id.as_str());
+                component.push(member);
+                if member.mod_id == id {
+                    break;
+                }
+            }
+            self.components.push(component);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cycle_reported_once_without_prefix() {
+        let mods = [
+            ModMetadata::new("entry", "1.0.0").with_dependency("a", "*", true),
+            ModMetadata::new("other_entry", "1.0.0").with_dependency("b", "*", true),
+            ModMetadata::new("a", "1.0.0").with_dependency("b", "*", true),
+            ModMetadata::new("b", "1.0.0").with_dependency("c", "*", true),
+            ModMetadata::new("c", "1.0.0").with_dependency("a", "*", true),
+        ];
+        let refs: Vec<_> = mods.iter().collect();
+        let graph = DependencyGraph::new(&refs);
+
+        assert_eq!(graph.cycles(), vec![vec!["a".to_string(), "b".to_string(), "c".to_string()]]);
+    }
+
+    #[test]
+    fn test_optional_mutual_dependency_is_not_a_cycle() {
+        let mods = [
+            ModMetadata::new("a", "1.0.0").with_dependency("b", "*", false),
+            ModMetadata::new("b", "1.0.0").with_dependency("a", "*", true),
+            ModMetadata::new("minecraft_user", "1.0.0").with_dependency("minecraft", "*", true),
+        ];
+        let refs: Vec<_> = mods.iter().collect();
+        let graph = DependencyGraph::new(&refs);
+
+        assert!(graph.cycles().is_empty());
+    }
+
+    #[test]
+    fn test_self_dependency_is_a_cycle() {
+        let mods = [ModMetadata::new("a", "1.0.0").with_dependency("a", "*", true)];
+        let refs: Vec<_> = mods.iter().collect();
+        let graph = DependencyGraph::new(&refs);
+
+        assert_eq!(graph.cycles(), vec![vec!["a".to_string()]]);
+    }
+
+    #[test]
+    fn test_paths_to() {
+        let mods = [
+            ModMetadata::new("app", "1.0.0").with_dependency("ui", "*", true).with_dependency("lib", "*", false),
+            ModMetadata::new("ui", "1.0.0").with_dependency("lib", "*", true),
+            ModMetadata::new("lib", "1.0.0"),
+        ];
+        let refs: Vec<_> = mods.iter().collect();
+        let graph = DependencyGraph::new(&refs);
+
+        let paths: Vec<Vec<_>> = graph.paths_to(&mods[2])
+            .iter()
+            .map(|path| path.iter().map(|edge| (edge.from.mod_id.as_str(), edge.dependency.mandatory)).collect())
+            .collect();
+        assert_eq!(paths, vec![vec![("app", false)], vec![("app", true), ("ui", true)]]);
+        assert!(graph.paths_to(&mods[0]).is_empty());
+    }
+}
@@ -2,11 +2,23 @@ mod forge;
 mod fabric;
 mod version;
 mod neoforge;
+mod graph;
+mod bridge;
+mod orphans;
+mod environment;
+mod fix;
+mod parser;
 
-pub use forge::parse_forge_mod_contents;
-pub use fabric::parse_fabric_mod_contents;
-pub use neoforge::parse_neoforge_mod_contents;
-use crate::r#mod::version::VersionConstraint;
+pub use forge::ForgeParser;
+pub use fabric::FabricParser;
+pub use neoforge::NeoForgeParser;
+pub use parser::{ModParser, ParserRegistry};
+pub use graph::{DependencyGraph, Edge};
+pub use orphans::find_orphans;
+pub use environment::{check_environment, Environment};
+pub use fix::{suggest_fixes, Direction, Fix, Likeness, Suggestion};
+pub use version::compare_versions;
+use crate::r#mod::version::{normalize_version, Interval, VersionConstraint};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::fmt;
@@ -22,6 +34,87 @@ pub struct ModMetadata {
     pub file_name: String,
     pub platform: Platform,
     pub dependencies: Vec<ModDependency>,
+    /// Whether the metadata marks the mod as a library for other mods.
+    #[serde(default)]
+    pub library: bool,
+    #[serde(default)]
+    pub side: Side,
+    /// Other mod ids the mod also satisfies dependencies on.
+    #[serde(default)]
+    pub provides: Vec<String>,
+}
+
+impl ModMetadata {
+    /// A Fabric mod with nothing but an id and a version, in `<mod_id>-<version>.jar`.
+    /// The `with_*` methods fill in the rest, e.g. for metadata formats of your own.
+    pub fn new(mod_id: &str, version: &str) -> Self {
+        ModMetadata {
+            mod_id: mod_id.to_string(),
+            version: version.to_string(),
+            name: None,
+            description: None,
+            authors: Vec::new(),
+            file_name: format!("{}-{}.jar", mod_id, version),
+            platform: Platform::Fabric,
+            dependencies: Vec::new(),
+            library: false,
+            side: Side::Both,
+            provides: Vec::new(),
+        }
+    }
+
+    pub fn with_file_name(mut self, file_name: &str) -> Self {
+        self.file_name = file_name.to_string();
+        self
+    }
+
+    pub fn with_platform(mut self, platform: Platform) -> Self {
+        self.platform = platform;
+        self
+    }
+
+    pub fn with_side(mut self, side: Side) -> Self {
+        self.side = side;
+        self
+    }
+
+    pub fn with_library(mut self, library: bool) -> Self {
+        self.library = library;
+        self
+    }
+
+    pub fn with_provides(mut self, mod_ids: &[&str]) -> Self {
+        self.provides.extend(mod_ids.iter().map(|mod_id| mod_id.to_string()));
+        self
+    }
+
+    /// Adds a dependency on each `(mod_id, version_range)`.
+    pub fn with_dependencies(self, dependencies: &[(&str, &str)]) -> Self {
+        dependencies.iter().fold(self, |mod_, (mod_id, range)| mod_.with_dependency(mod_id, range, true))
+    }
+
+    /// Adds an optional dependency on each `(mod_id, version_range)`.
+    pub fn with_optional_dependencies(self, dependencies: &[(&str, &str)]) -> Self {
+        dependencies.iter().fold(self, |mod_, (mod_id, range)| mod_.with_dependency(mod_id, range, false))
+    }
+
+    pub fn with_dependency(mut self, mod_id: &str, version_range: &str, mandatory: bool) -> Self {
+        self.dependencies.push(ModDependency {
+            mod_id: mod_id.to_string(),
+            version_range: DependencyVersionRange::Single(version_range.to_string()),
+            mandatory,
+        });
+        self
+    }
+}
+
+/// The physical side a mod has to be installed on.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Side {
+    #[default]
+    Both,
+    Client,
+    Server,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
@@ -33,6 +126,20 @@ pub enum Platform {
     Unknown(String),
 }
 
+impl std::str::FromStr for Platform {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "forge" => Ok(Platform::Forge),
+            "fabric" => Ok(Platform::Fabric),
+            "neoforge" => Ok(Platform::NeoForge),
+            "quilt" => Ok(Platform::Quilt),
+            _ => Err(format!("Unknown loader: {}. Expected one of: forge, fabric, neoforge, quilt", s)),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(untagged)]
 pub enum DependencyVersionRange {
@@ -40,6 +147,56 @@ pub enum DependencyVersionRange {
     Multiple(Vec<String>),
 }
 
+impl fmt::Display for DependencyVersionRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DependencyVersionRange::Single(s) => write!(f, "{}", s),
+            DependencyVersionRange::Multiple(v) => write!(f, "{}", v.join(" || ")),
+        }
+    }
+}
+
+impl DependencyVersionRange {
+    /// Whether `version` satisfies the range. Unparsable versions or ranges never match.
+    pub fn matches(&self, version: &str) -> bool {
+        let Ok(version) = Version::parse(version) else { return false };
+        let matches = |range: &String| range.parse::<VersionConstraint>()
+            .is_ok_and(|constraint| constraint.matches(&version));
+
+        match self {
+            DependencyVersionRange::Single(range) => matches(range),
+            DependencyVersionRange::Multiple(ranges) => ranges.iter().any(matches),
+        }
+    }
+
+    /// Like `matches`, but assumes that versions or ranges this tool can't
+    /// parse match, and accepts versions without a patch number like `1.21`.
+    pub fn allows(&self, version: &str) -> bool {
+        let Ok(version) = Version::parse(&normalize_version(version)) else { return true };
+
+        let ranges = match self {
+            DependencyVersionRange::Single(range) => std::slice::from_ref(range),
+            DependencyVersionRange::Multiple(ranges) => ranges.as_slice(),
+        };
+        let constraints: Result<Vec<_>, _> = ranges.iter().map(|r| r.parse::<VersionConstraint>()).collect();
+        match constraints {
+            Ok(constraints) => constraints.iter().any(|c| c.matches(&version)),
+            Err(_) => true,
+        }
+    }
+
+    /// The versions the range allows, or `None` if part of it can't be parsed.
+    pub fn intervals(&self) -> Option<Vec<Interval>> {
+        let ranges = match self {
+            DependencyVersionRange::Single(range) => std::slice::from_ref(range),
+            DependencyVersionRange::Multiple(ranges) => ranges.as_slice(),
+        };
+        ranges.iter()
+            .map(|range| range.parse::<VersionConstraint>().ok().map(|constraint| constraint.interval()))
+            .collect()
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ModDependency {
     pub mod_id: String,
@@ -47,13 +204,47 @@ pub struct ModDependency {
     pub mandatory: bool,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum DependencyError {
     UnsupportedPlatform(Platform, Vec<String>),
-    MissingDependency(String, String, String),
+    /// A mandatory dependency no mod provides, as `(mod_id, file_name, dependency_id, suggestion)`,
+    /// where `suggestion` is the installed mod it most likely is under another id.
+    MissingDependency(String, String, String, Option<Suggestion>),
     VersionConflict(String, String, String, String, String),
     CircularDependency(Vec<String>),
     InvalidVersionFormat(String, String, String),
+    /// Mods of a platform the instance's loader won't load, as `(platform, loader, file_names)`.
+    WrongLoader(Platform, Platform, Vec<String>),
+    /// A mod a bridge is known not to support, as `(mod_id, file_name, reason)`.
+    UnsupportedByBridge(String, String, String),
+    /// A mod id declared by several jars, as `(file_name, version)` pairs sorted newest first.
+    DuplicateMod(String, Vec<(String, String)>),
+    /// A game or loader version the mod doesn't accept, as `(mod_id, file_name, dependency_id, required, target)`.
+    IncompatibleEnvironment(String, String, String, String, String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The game won't start, or the affected mods won't work.
+    Error,
+    /// Worth checking, but the game can start.
+    Warning,
+}
+
+impl DependencyError {
+    pub fn severity(&self) -> Severity {
+        match self {
+            DependencyError::MissingDependency(..)
+            | DependencyError::VersionConflict(..)
+            | DependencyError::CircularDependency(_)
+            | DependencyError::UnsupportedByBridge(..)
+            | DependencyError::DuplicateMod(..)
+            | DependencyError::IncompatibleEnvironment(..) => Severity::Error,
+            DependencyError::UnsupportedPlatform(..)
+            | DependencyError::InvalidVersionFormat(..)
+            | DependencyError::WrongLoader(..) => Severity::Warning,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -77,33 +268,142 @@ impl fmt::Display for DependencyError {
             DependencyError::UnsupportedPlatform(platform, file_name) => {
                 write!(f, "Unsupported platform: {:?}\n {:?}", platform, file_name)
             }
-            DependencyError::MissingDependency(mod_id, file_name, dependency_id) => {
-                write!(f, "Missing dependency for {} ({}): {}", mod_id, file_name, dependency_id)
+            DependencyError::MissingDependency(mod_id, file_name, dependency_id, suggestion) => {
+                write!(f, "Missing dependency for {} ({}): {}", mod_id, file_name, dependency_id)?;
+                match suggestion {
+                    Some(suggestion) => write!(f, ", {}", suggestion),
+                    None => Ok(()),
+                }
             }
             DependencyError::VersionConflict(file_name, mod_id, required, found, found_name) => write!(
                 f,
                 "Version conflict for {}:\n    required {} {}, found {} ({}) ",
                 file_name, mod_id, required, found, found_name
             ),
-            DependencyError::CircularDependency(chain) => {
-                write!(f, "Circular dependency detected: {}", chain.join(" -> "))
+            DependencyError::CircularDependency(members) => {
+                write!(f, "Circular dependency detected between: {}", members.join(", "))
             }
             DependencyError::InvalidVersionFormat(mod_id, file_name, version_str) => write!(
                 f,
                 "Invalid version format for {} ({}): \"{}\"",
                 mod_id, file_name, version_str
             ),
+            DependencyError::WrongLoader(platform, loader, file_names) => {
+                write!(f, "{:?} mods won't be loaded by a {:?} instance:", platform, loader)?;
+                for file_name in file_names {
+                    write!(f, "\n    {}", file_name)?;
+                }
+                Ok(())
+            }
+            DependencyError::UnsupportedByBridge(mod_id, file_name, reason) => write!(
+                f,
+                "{} ({}) is known not to work with Sinytra Connector: {}",
+                mod_id, file_name, reason
+            ),
+            DependencyError::DuplicateMod(mod_id, files) => {
+                write!(f, "Duplicate mod {} found in {} files:", mod_id, files.len())?;
+                for (file_name, version) in files {
+                    write!(f, "\n    {} ({})", file_name, version)?;
+                }
+                let (_, newest_version) = &files[0];
+                let older: Vec<_> = files.iter()
+                    .filter(|(_, version)| compare_versions(version, newest_version).is_lt())
+                    .map(|(file_name, _)| file_name.as_str())
+                    .collect();
+                if !older.is_empty() {
+                    write!(f, "\n    older, likely left over: {}", older.join(", "))?;
+                }
+                Ok(())
+            }
+            DependencyError::IncompatibleEnvironment(mod_id, file_name, dependency_id, required, target) => write!(
+                f,
+                "{} ({}) requires {} {}, but the instance targets {}",
+                mod_id, file_name, dependency_id, required, target
+            ),
         }
     }
 }
 
+/// Picks the loader most jars are built for.
+///
+/// Multi-loader jars count towards every platform they ship metadata for.
+pub fn detect_loader(mods: &[ModMetadata]) -> Option<Platform> {
+    let mut jars: HashMap<&Platform, HashSet<&str>> = HashMap::new();
+    for mod_ in mods {
+        jars.entry(&mod_.platform).or_default().insert(mod_.file_name.as_str());
+    }
+
+    jars.into_iter()
+        .filter(|(platform, _)| !matches!(platform, Platform::Unknown(_)))
+        .max_by(|(a_platform, a_jars), (b_platform, b_jars)| {
+            a_jars.len().cmp(&b_jars.len())
+                .then_with(|| format!("{:?}", b_platform).cmp(&format!("{:?}", a_platform)))
+        })
+        .map(|(platform, _)| platform.clone())
+}
+
+/// Keeps, for every jar that ships metadata for several platforms, only the
+/// entries of `loader`. Jars with no entry for `loader` are left untouched.
+pub fn select_loader_entries(mods: Vec<ModMetadata>, loader: &Platform) -> Vec<ModMetadata> {
+    let jars_for_loader: HashSet<String> = mods.iter()
+        .filter(|m| &m.platform == loader)
+        .map(|m| m.file_name.clone())
+        .collect();
+
+    mods.into_iter()
+        .filter(|m| &m.platform == loader || !jars_for_loader.contains(&m.file_name))
+        .collect()
+}
+
+/// Builds the dependency graph of the mods a `loader` instance loads,
+/// or of every mod when the loader is unknown.
+pub fn dependency_graph<'a>(mods: &'a [ModMetadata], loader: Option<&Platform>) -> DependencyGraph<'a> {
+    let Some(loader) = loader else {
+        let all: Vec<_> = mods.iter().collect();
+        return DependencyGraph::new(&all);
+    };
+
+    let bridged = bridge::bridged_platforms(mods, loader);
+    let loaded: Vec<_> = mods.iter()
+        .filter(|m| &m.platform == loader || bridged.contains(&m.platform))
+        .collect();
+    let aliases = if bridge::has_connector(mods, loader) {
+        bridge::connector_aliases(&loaded)
+    } else {
+        HashMap::new()
+    };
+
+    DependencyGraph::new(&loaded).with_aliases(aliases)
+}
+
+/// Every problem of `mods`, including game and loader versions `environment` doesn't satisfy.
+pub fn diagnose(mods: &[ModMetadata], loader: Option<&Platform>, environment: &Environment) -> Vec<DependencyError> {
+    let mut errors = analyze_dependencies(mods, loader).err().map(|e| e.0).unwrap_or_default();
+    errors.extend(check_environment(mods, environment));
+    errors
+}
+
+/// Resolves the dependencies of `mods` as loaded by a `loader` instance.
+///
+/// Mods of other platforms are reported as not loaded, unless the loader
+/// runs them natively or through a bridge, in which case they are resolved
+/// together with the loader's own mods. Without a `loader`, every platform
+/// is resolved on its own.
 pub fn analyze_dependencies(
     mods: &[ModMetadata],
+    loader: Option<&Platform>,
 ) -> Result<Vec<ModMetadata>, DependencyErrors> {
+    let bridged = loader.map(|loader| bridge::bridged_platforms(mods, loader)).unwrap_or_default();
+    let connector = loader.is_some_and(|loader| bridge::has_connector(mods, loader));
+
     let mut platform_groups: HashMap<Platform, Vec<&ModMetadata>> = HashMap::new();
     for mod_ in mods {
+        let platform = match loader {
+            Some(loader) if bridged.contains(&mod_.platform) => loader.clone(),
+            _ => mod_.platform.clone(),
+        };
         platform_groups
-            .entry(mod_.platform.clone())
+            .entry(platform)
             .or_default()
             .push(mod_);
     }
@@ -113,8 +413,22 @@ pub fn analyze_dependencies(
 
     for (platform, platform_mods) in platform_groups {
         match platform {
-            Platform::Forge | Platform::Fabric | Platform::NeoForge => {
-                match resolve_dependencies(platform_mods) {
+            Platform::Forge | Platform::Fabric | Platform::NeoForge | Platform::Quilt
+                if loader.is_some_and(|loader| loader != &platform) => {
+                all_errors.push(DependencyError::WrongLoader(
+                    platform,
+                    loader.unwrap().clone(),
+                    platform_mods.iter().map(|m| m.file_name.clone()).collect(),
+                ));
+            }
+            Platform::Forge | Platform::Fabric | Platform::NeoForge | Platform::Quilt => {
+                let aliases = if connector && loader == Some(&platform) {
+                    all_errors.extend(bridge::connector_incompatibilities(&platform_mods));
+                    bridge::connector_aliases(&platform_mods)
+                } else {
+                    HashMap::new()
+                };
+                match resolve_dependencies(platform_mods, aliases) {
                     Ok(resolved) => result.extend(resolved.into_iter().cloned()),
                     Err(errors) => all_errors.extend(errors.0),
                 }
@@ -135,32 +449,38 @@ pub fn analyze_dependencies(
     }
 }
 
+/// Whether `mod_id` refers to the game, the loader or the runtime rather than to a mod.
+pub fn is_platform_dependency(mod_id: &str) -> bool {
+    matches!(mod_id, "minecraft" | "forge" | "fabricloader" | "fabric-resource-loader-v0" | "java" | "neoforge")
+}
+
 fn resolve_dependencies(
     mods: Vec<&ModMetadata>,
+    aliases: HashMap<String, String>,
 ) -> Result<Vec<&ModMetadata>, DependencyErrors> {
-    let mod_map: HashMap<_, _> = mods
-        .iter()
-        .map(|m| (m.mod_id.as_str(), *m))
-        .collect();
+    let graph = DependencyGraph::new(&mods).with_aliases(aliases);
 
     let mut resolved = HashSet::new();
     let mut ordered = Vec::new();
-    let mut errors = Vec::new();
+    let mut errors = find_duplicates(&mods);
 
     for mod_ in mods.iter() {
         if !resolved.contains(&mod_.mod_id) {
             resolve_mod(
                 mod_,
-                &mod_map,
+                &graph,
                 &mut resolved,
                 &mut HashSet::new(),
                 &mut ordered,
-                &mut vec![mod_.mod_id.clone()],
                 &mut errors,
             );
         }
     }
 
+    for members in graph.cycles() {
+        errors.push(DependencyError::CircularDependency(members));
+    }
+
     if errors.is_empty() {
         Ok(ordered)
     } else {
@@ -168,34 +488,48 @@ fn resolve_dependencies(
     }
 }
 
+/// Reports every `mod_id` that is declared by more than one jar.
+fn find_duplicates(mods: &[&ModMetadata]) -> Vec<DependencyError> {
+    let mut by_id: HashMap<&str, Vec<&ModMetadata>> = HashMap::new();
+    let mut ids = Vec::new();
+    for mod_ in mods {
+        let entries = by_id.entry(mod_.mod_id.as_str()).or_default();
+        if entries.is_empty() {
+            ids.push(mod_.mod_id.as_str());
+        }
+        if !entries.iter().any(|m| m.file_name == mod_.file_name) {
+            entries.push(mod_);
+        }
+    }
+
+    ids.into_iter()
+        .filter(|id| by_id[id].len() > 1)
+        .map(|id| {
+            let mut files: Vec<_> = by_id[id].iter()
+                .map(|m| (m.file_name.clone(), m.version.clone()))
+                .collect();
+            files.sort_by(|(_, a), (_, b)| compare_versions(b, a));
+            DependencyError::DuplicateMod(id.to_string(), files)
+        })
+        .collect()
+}
+
 fn resolve_mod<'a>(
     mod_: &'a ModMetadata,
-    mod_map: &HashMap<&str, &'a ModMetadata>,
+    graph: &DependencyGraph<'a>,
     resolved: &mut HashSet<String>,
     unresolved: &mut HashSet<String>,
     ordered: &mut Vec<&'a ModMetadata>,
-    path: &mut Vec<String>,
     errors: &mut Vec<DependencyError>,
 ) {
     unresolved.insert(mod_.mod_id.clone());
 
     for dep in &mod_.dependencies {
-        if matches!(dep.mod_id.as_str(), "minecraft" | "forge" | "fabricloader" | "fabric-resource-loader-v0" | "java" | "neoforge") {
-            continue;
-        }
-
-        if resolved.contains(&dep.mod_id) {
-            continue;
-        }
-
-        if unresolved.contains(&dep.mod_id) {
-            let mut cycle = path.clone();
-            cycle.push(dep.mod_id.clone());
-            errors.push(DependencyError::CircularDependency(cycle));
+        if is_platform_dependency(&dep.mod_id) {
             continue;
         }
 
-        let dep_mod = match mod_map.get(dep.mod_id.as_str()) {
+        let dep_mod = match graph.get(&dep.mod_id) {
             Some(m) => m,
             None => {
                 if dep.mandatory {
@@ -203,20 +537,27 @@ fn resolve_mod<'a>(
                         mod_.mod_id.clone(),
                         mod_.file_name.clone(),
                         dep.mod_id.clone(),
+                        fix::suggest_mod(graph.nodes().iter().copied(), &dep.mod_id),
                     ));
                 }
                 continue;
             }
         };
 
+        // Every dependent's range is checked, even once the dependency is resolved.
+        let visited = resolved.contains(&dep_mod.mod_id) || unresolved.contains(&dep_mod.mod_id);
+
         let current_mod_version = match Version::parse(&dep_mod.version) {
             Ok(v) => v,
             Err(_) => {
-                errors.push(DependencyError::InvalidVersionFormat(
+                let error = DependencyError::InvalidVersionFormat(
                     dep_mod.mod_id.clone(),
                     dep_mod.file_name.clone(),
                     dep_mod.version.clone(),
-                ));
+                );
+                if !errors.contains(&error) {
+                    errors.push(error);
+                }
                 continue;
             }
         };
@@ -262,25 +603,100 @@ fn resolve_mod<'a>(
         }
 
         if !version_matched {
-            let required_display = match &dep.version_range {
-                DependencyVersionRange::Single(s) => s.clone(),
-                DependencyVersionRange::Multiple(v) => v.join(" || "),
-            };
             errors.push(DependencyError::VersionConflict(
                 mod_.file_name.clone(),
                 dep.mod_id.clone(),
-                required_display,
+                dep.version_range.to_string(),
                 dep_mod.version.clone(),
                 dep_mod.file_name.clone(),
             ));
         }
 
-        path.push(dep.mod_id.clone());
-        resolve_mod(dep_mod, mod_map, resolved, unresolved, ordered, path, errors);
-        path.pop();
+        // Cycles are reported once per strongly connected component by `resolve_dependencies`.
+        if !visited {
+            resolve_mod(dep_mod, graph, resolved, unresolved, ordered, errors);
+        }
     }
 
     resolved.insert(mod_.mod_id.clone());
     unresolved.remove(&mod_.mod_id);
     ordered.push(mod_);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_range_accepts_any_version() {
+        let mods = [
+            ModMetadata::new("app", "1.0.0")
+                .with_platform(Platform::Forge)
+                .with_dependencies(&[("lib", "")]),
+            ModMetadata::new("lib", "3.2.1").with_platform(Platform::Forge),
+        ];
+        assert!(analyze_dependencies(&mods, Some(&Platform::Forge)).is_ok());
+    }
+
+    fn mixed() -> Vec<ModMetadata> {
+        vec![
+            ModMetadata::new("create", "0.5.1").with_platform(Platform::Forge),
+            ModMetadata::new("jei", "15.2.0").with_platform(Platform::Forge),
+            ModMetadata::new("sodium", "0.5.8"),
+            ModMetadata::new("lithium", "0.11.2"),
+            ModMetadata::new("flywheel", "0.6.10").with_platform(Platform::Forge),
+        ]
+    }
+
+    #[test]
+    fn test_detect_loader_picks_the_most_common_platform() {
+        assert_eq!(detect_loader(&mixed()), Some(Platform::Forge));
+        assert_eq!(detect_loader(&[]), None);
+
+        // A multi-loader jar counts once for each of its platforms.
+        let mut mods = mixed();
+        mods.push(ModMetadata::new("architectury", "9.1.0").with_file_name("architectury.jar"));
+        mods.push(ModMetadata::new("architectury", "9.1.0").with_file_name("architectury.jar").with_platform(Platform::Forge));
+        assert_eq!(detect_loader(&mods), Some(Platform::Forge));
+    }
+
+    #[test]
+    fn test_select_loader_entries_keeps_one_entry_per_jar() {
+        let mods = vec![
+            ModMetadata::new("architectury", "9.1.0").with_file_name("architectury.jar"),
+            ModMetadata::new("architectury", "9.1.0").with_file_name("architectury.jar").with_platform(Platform::Forge),
+            ModMetadata::new("architectury", "9.1.0").with_file_name("architectury.jar").with_platform(Platform::NeoForge),
+            ModMetadata::new("sodium", "0.5.8"),
+        ];
+        let selected = select_loader_entries(mods, &Platform::Forge);
+        let entries: Vec<_> = selected.iter().map(|m| (m.file_name.as_str(), &m.platform)).collect();
+        assert_eq!(entries, [("architectury.jar", &Platform::Forge), ("sodium-0.5.8.jar", &Platform::Fabric)]);
+    }
+
+    #[test]
+    fn test_wrong_loader_groups_the_jars_of_each_platform() {
+        // Without a loader, every platform is resolved on its own.
+        assert!(analyze_dependencies(&mixed(), None).is_ok());
+
+        let errors = analyze_dependencies(&mixed(), Some(&Platform::Forge)).unwrap_err().0;
+        assert_eq!(errors, [DependencyError::WrongLoader(
+            Platform::Fabric,
+            Platform::Forge,
+            vec!["sodium-0.5.8.jar".to_string(), "lithium-0.11.2.jar".to_string()],
+        )]);
+        assert_eq!(errors[0].severity(), Severity::Warning);
+
+        let errors = analyze_dependencies(&mixed(), Some(&Platform::Fabric)).unwrap_err().0;
+        assert!(matches!(
+            errors.as_slice(),
+            [DependencyError::WrongLoader(Platform::Forge, Platform::Fabric, file_names)] if file_names.len() == 3
+        ));
+    }
+
+    #[test]
+    fn test_provided_ids_satisfy_dependencies() {
+        let provider = ModMetadata::new("fabric-api", "0.92.0").with_provides(&["fabric"]);
+        let mods = vec![ModMetadata::new("app", "1.0.0").with_dependencies(&[("fabric", "*")]), provider];
+        assert!(analyze_dependencies(&mods, Some(&Platform::Fabric)).is_ok());
+    }
+}
@@ -1,9 +1,8 @@
-use std::fs::File;
 use serde::Deserialize;
-use anyhow::{Context, Result};
-use zip::ZipArchive;
-use std::io::Read;
-use crate::r#mod::{DependencyVersionRange, ModDependency, ModMetadata, Platform};
+use crate::error::{Error, Result};
+use crate::jar::{self, Archive};
+use crate::r#mod::parser::ModParser;
+use crate::r#mod::{DependencyVersionRange, ModDependency, ModMetadata, Platform, Side};
 use super::forge::{Authors, parse_authors};
 
 // https://docs.neoforged.net/docs/gettingstarted/modfiles#neoforgemodstoml
@@ -16,38 +15,24 @@ pub struct NeoForgeMod {
     /// such as an interface or method.
     /// NeoForge provides the Java loader `javafml`.
     #[serde(rename = "modLoader")]
+    #[allow(dead_code)]
     pub mod_loader: String,
     /// The acceptable version range of the language loader,
     /// expressed as a Maven Version Range.
     /// For `javafml`, this is currently version 1.
     /// If no version is specified,then any version of the mod loader can be used.
     #[serde(rename = "loaderVersion")]
+    #[allow(dead_code)]
     pub loader_version: String,
     /// The license the mod(s) in this JAR are provided under.
     #[serde(rename = "license")]
+    #[allow(dead_code)]
     pub license: String,
-    /// When `true`, the mod(s)’s resources will be displayed as
-    /// a separate resource pack on the ‘Resource Packs’ menu,
-    /// rather than being combined with the `Mod resources` pack.
-    #[serde(rename = "showAsResourcePack")]
-    pub show_as_resource_pack: Option<bool>,
-    /// When `true`, the mod(s)’s data file will be displayed as
-    /// a separate data pack on the ‘Data Packs’ menu,
-    /// rather than being combined with the `Mod Data` pack.
-    #[serde(rename = "showAsDataPack")]
-    pub show_as_data_pack: Option<bool>,
-    /// An array of services your mod uses.
-    /// This is consumed as part of the created module for the mod
-    /// from NeoForge's implementation of the Java Platform Module System.
-    pub services: Option<Vec<String>>,
     /// A table of substitution properties.
     /// This is used by `StringSubstitutor` to replace `${file.<key>}` with its corresponding value.
     // pub properties,
     /// Mod-specific properties are tied to the specified mod using the `[[mods]]` header.
     /// This is an array of tables;
-    /// A URL representing the place to report and track issues with the mod(s).
-    #[serde(rename = "issueTrackerURL")]
-    pub issue_tracker_url: Option<String>,
     /// all key/value properties will be attached to that mod until the next header.
     #[serde(rename = "mods")]
     pub mods: Vec<ModEntry>,
@@ -81,20 +66,6 @@ pub struct ModEntry {
     /// The description of the mod shown in the mod list screen.
     #[serde(rename = "description")]
     pub description: Option<String>,
-    /// The name and extension of an image file used on the mods list screen.
-    /// The location must be an absolute path starting from the root of the JAR or source set
-    /// (e.g. ·src/main/resources` for the main source set).
-    /// Valid filename characters are lowercase letters (a-z), digits (0-9), slashes, (/),
-    /// underscores (_), periods (.) and hyphens (-). The complete character set is [a-z0-9_-.].
-    #[serde(rename = "logoFile")]
-    pub logo_file: Option<String>,
-    /// Whether to use `GL_LINEAR*` (true) or `GL_NEAREST*` (false) to render the logoFile.
-    #[serde(rename = "logoBlur")]
-    pub logo_blur: Option<bool>,
-    /// A URL to a JSON used by the [update checker](https://docs.neoforged.net/docs/misc/updatechecker/)
-    /// to make sure the mod you are playing is the latest version.
-    #[serde(rename = "updateJSONURL")]
-    pub update_json_url: Option<String>,
     // pub features,
     /// A table of key/values associated with this mod.
     /// Unused by NeoForge, but is mainly for use by mods.
@@ -102,21 +73,9 @@ pub struct ModEntry {
     /// A URL to the download page of the mod. Currently unused.
     // #[serde(rename = "modUrl")]
     // pub mod_url: Option<String>,
-    /// Credits and acknowledges for the mod shown on the mod list screen.
-    #[serde(rename = "credits")]
-    pub credits: Option<String>,
     /// The authors of the mod shown on the mod list screen.
     #[serde(rename = "authors")]
     pub authors: Option<Authors>,
-    /// A URL to the display page of the mod shown on the mod list screen.
-    #[serde(rename = "displayURL")]
-    pub display_url: Option<String>,
-    /// The file path of a JSON file used for enum extension
-    #[serde(rename = "enumExtensions")]
-    pub enum_extensions: Option<String>,
-    /// The file path of a JSON file used for feature flags
-    #[serde(rename = "featureFlags")]
-    pub feature_flags: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -139,10 +98,6 @@ pub struct DependencyEntry {
     /// `"discouraged"` still allows the mod to load if the dependency is present, but presents a warning to the user.
     #[serde(rename = "type")]
     pub r#type: String,
-    /// An optional user-facing message to describe why this dependency is required,
-    /// or why it is incompatible.
-    #[serde(rename = "reason")]
-    pub reason: Option<String>,
     /// The acceptable version range of the language loader,
     /// expressed as a Maven Version Range.
     /// An empty string matches any version.
@@ -150,34 +105,54 @@ pub struct DependencyEntry {
     pub version_range: String,
     /// Defines if the mod must load before ("BEFORE") or after ("AFTER") this dependency.
     /// If the ordering does not matter, return "NONE"
+    #[allow(dead_code)]
     pub ordering: String,
     /// The physical side the dependency must be present on: "CLIENT", "SERVER", or "BOTH".
+    #[allow(dead_code)]
     pub side: String,
     // A URL to the download page of the dependency. Currently unused.
     // #[serde(rename = "referralUrl")]
     // pub referral_url: Option<String>,
 }
 
-pub fn parse_neoforge_mod_contents(jar_file: &mut ZipArchive<File>, file_name: &String) -> Result<Vec<ModMetadata>> {
-    let mut file = jar_file.by_name("META-INF/neoforge.mods.toml")?;
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
-    drop(file);
+const METADATA_FILE: &str = "META-INF/neoforge.mods.toml";
+
+/// Reads the `META-INF/neoforge.mods.toml` of NeoForge mods.
+pub struct NeoForgeParser;
+
+impl ModParser for NeoForgeParser {
+    fn name(&self) -> &str {
+        "META-INF/neoforge.mods.toml (NeoForge)"
+    }
+
+    fn detect(&self, archive: &mut dyn Archive) -> bool {
+        archive.contains(METADATA_FILE)
+    }
+
+    fn parse(&self, archive: &mut dyn Archive, file_name: &str) -> Result<Vec<ModMetadata>> {
+        parse_neoforge_mod_contents(archive, file_name)
+    }
+}
+
+fn parse_neoforge_mod_contents(jar_file: &mut dyn Archive, file_name: &str) -> Result<Vec<ModMetadata>> {
+    let contents = jar::read_entry(jar_file, METADATA_FILE, file_name)?;
     let toml: NeoForgeMod = toml::from_str(contents.as_str())
-        .with_context(|| format!("Failed to parse NeoForge mods.toml from {}", file_name))?;
+        .map_err(|e| Error::metadata(file_name, METADATA_FILE, e))?;
+
+    let library = matches!(
+        jar::manifest_attribute(jar_file, "FMLModType").as_deref(),
+        Some("LIBRARY" | "GAMELIBRARY")
+    );
 
     let mut all_metadata = Vec::new();
 
     for mod_entry in &toml.mods {
         let version = if mod_entry.version == "${file.jarVersion}" {
-            let mut manifest_file = jar_file.by_name("META-INF/MANIFEST.MF")
-                .with_context(|| "META-INF/MANIFEST.MF not found in JAR")?;
-            let mut manifest_contents = String::new();
-            manifest_file.read_to_string(&mut manifest_contents)?;
+            let manifest_contents = jar::read_entry(jar_file, "META-INF/MANIFEST.MF", file_name)?;
 
             let version_line = manifest_contents.lines()
                 .find(|line| line.starts_with("Implementation-Version:"))
-                .with_context(|| "Implementation-Version not found in MANIFEST.MF")?;
+                .ok_or_else(|| Error::metadata(file_name, "META-INF/MANIFEST.MF", "Implementation-Version not found"))?;
             version_line.split(": ").nth(1).unwrap_or("unknown").to_string()
         } else {
             mod_entry.version.clone()
@@ -191,7 +166,10 @@ pub fn parse_neoforge_mod_contents(jar_file: &mut ZipArchive<File>, file_name: &
             authors: parse_authors(&mod_entry.authors),
             platform: Platform::NeoForge,
             dependencies: parse_neoforge_dependencies(&toml),
-            file_name: file_name.clone(),
+            file_name: file_name.to_string(),
+            library,
+            side: Side::Both,
+            provides: Vec::new(),
         };
         all_metadata.push(metadata);
     }
@@ -255,7 +233,6 @@ config="entityculling.mixins.json"
 "#;
         let file_name = "test.toml".to_string();
         let toml: NeoForgeMod = toml::from_str(toml_content)
-            .with_context(|| format!("Failed to parse Forge mods.toml from {}", file_name))
             .unwrap();
 
         let mut all_metadata = Vec::new();
@@ -270,6 +247,9 @@ config="entityculling.mixins.json"
                 platform: Platform::Forge,
                 dependencies: parse_neoforge_dependencies(&toml),
                 file_name: file_name.clone(),
+                library: false,
+                side: Side::Both,
+                provides: Vec::new(),
             };
             all_metadata.push(metadata);
         }
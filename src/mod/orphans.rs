@@ -0,0 +1,67 @@
+use std::collections::HashSet;
+use crate::r#mod::{DependencyGraph, ModMetadata};
+
+/// Finds library mods that no other mod depends on, even optionally.
+///
+/// A mod counts as a library when its metadata says so (see `ModMetadata::library`)
+/// or when its id is listed in `extra_libraries`. Jars that also contain a mod
+/// something depends on are never reported, as removing them would remove that mod too.
+pub fn find_orphans<'a>(graph: &DependencyGraph<'a>, extra_libraries: &[String]) -> Vec<&'a ModMetadata> {
+    let needed_files: HashSet<&str> = graph.nodes()
+        .iter()
+        .filter(|m| !graph.dependents(m).is_empty())
+        .map(|m| m.file_name.as_str())
+        .collect();
+
+    let mut orphans: Vec<_> = graph.nodes()
+        .iter()
+        .copied()
+        .filter(|m| m.library || extra_libraries.contains(&m.mod_id))
+        .filter(|m| !needed_files.contains(m.file_name.as_str()))
+        .collect();
+    orphans.sort_by(|a, b| a.mod_id.cmp(&b.mod_id));
+    orphans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r#mod::Platform;
+
+    #[test]
+    fn test_find_orphans() {
+        let mods = [
+            ModMetadata::new("app", "1.0.0")
+                .with_file_name("app.jar")
+                .with_platform(Platform::Forge)
+                .with_dependency("used_lib", "", true)
+                .with_dependency("optional_lib", "", false),
+            ModMetadata::new("used_lib", "1.0.0")
+                .with_file_name("used_lib.jar")
+                .with_platform(Platform::Forge)
+                .with_library(true),
+            ModMetadata::new("optional_lib", "1.0.0")
+                .with_file_name("optional_lib.jar")
+                .with_platform(Platform::Forge)
+                .with_library(true),
+            ModMetadata::new("unused_lib", "1.0.0")
+                .with_file_name("unused_lib.jar")
+                .with_platform(Platform::Forge)
+                .with_library(true),
+            ModMetadata::new("listed_lib", "1.0.0").with_file_name("listed_lib.jar").with_platform(Platform::Forge),
+            ModMetadata::new("bundled_lib", "1.0.0")
+                .with_file_name("used_lib.jar")
+                .with_platform(Platform::Forge)
+                .with_library(true),
+            ModMetadata::new("standalone", "1.0.0").with_file_name("standalone.jar").with_platform(Platform::Forge),
+        ];
+        let refs: Vec<_> = mods.iter().collect();
+        let graph = DependencyGraph::new(&refs);
+
+        let orphans: Vec<_> = find_orphans(&graph, &["listed_lib".to_string()])
+            .iter()
+            .map(|m| m.mod_id.as_str())
+            .collect();
+        assert_eq!(orphans, vec!["listed_lib", "unused_lib"]);
+    }
+}
@@ -0,0 +1,84 @@
+use std::io::Cursor;
+use std::path::Path;
+use zip::ZipArchive;
+use crate::error::{Error, Result};
+use crate::jar::{self, Archive};
+use crate::r#mod::fabric::FabricParser;
+use crate::r#mod::forge::ForgeParser;
+use crate::r#mod::neoforge::NeoForgeParser;
+use crate::r#mod::ModMetadata;
+
+/// Reads the mods a jar declares in one metadata format.
+///
+/// Implement it and add it to a `ParserRegistry` to support formats other
+/// than Fabric's, Forge's and NeoForge's.
+pub trait ModParser: Send + Sync {
+    /// The metadata file the parser reads, e.g. `fabric.mod.json`, for error messages.
+    fn name(&self) -> &str;
+    /// Whether the jar has metadata of this format, usually by probing an entry.
+    fn detect(&self, archive: &mut dyn Archive) -> bool;
+    /// The mods the jar declares, named `file_name`. Only called on jars the
+    /// parser detected.
+    fn parse(&self, archive: &mut dyn Archive, file_name: &str) -> Result<Vec<ModMetadata>>;
+}
+
+/// The parsers jars are read with.
+///
+/// Every parser that detects a jar parses it, as multi-loader jars ship one
+/// metadata file per platform. The default registry has the Fabric, Forge and
+/// NeoForge parsers.
+pub struct ParserRegistry {
+    parsers: Vec<Box<dyn ModParser>>,
+}
+
+impl ParserRegistry {
+    /// A registry with no parser at all.
+    pub fn empty() -> Self {
+        ParserRegistry { parsers: Vec::new() }
+    }
+
+    pub fn register(&mut self, parser: impl ModParser + 'static) {
+        self.parsers.push(Box::new(parser));
+    }
+
+    /// Parses a jar that is already open, e.g. one read from a modpack.
+    pub fn parse_archive(&self, archive: &mut dyn Archive, file_name: &str) -> Result<Vec<ModMetadata>> {
+        let mut mods = Vec::new();
+        for parser in &self.parsers {
+            if parser.detect(archive) {
+                mods.extend(parser.parse(archive, file_name)?);
+            }
+        }
+
+        if mods.is_empty() {
+            return Err(Error::UnsupportedFormat {
+                name: file_name.to_string(),
+                expected: self.parsers.iter().map(|parser| parser.name().to_string()).collect(),
+            });
+        }
+        Ok(mods)
+    }
+
+    /// Parses a jar read in memory, e.g. from inside a modpack.
+    pub fn parse_bytes(&self, bytes: &[u8], file_name: &str) -> Result<Vec<ModMetadata>> {
+        let mut archive = ZipArchive::new(Cursor::new(bytes))
+            .map_err(|e| Error::archive(file_name, e))?;
+        self.parse_archive(&mut archive, file_name)
+    }
+
+    /// Parses the jar at `path`, named by its file name.
+    pub fn parse_file(&self, path: &Path) -> Result<Vec<ModMetadata>> {
+        let file_name = path.file_name().unwrap_or(path.as_os_str()).to_string_lossy().into_owned();
+        self.parse_archive(&mut jar::open_jar_file(path)?, &file_name)
+    }
+}
+
+impl Default for ParserRegistry {
+    fn default() -> Self {
+        let mut registry = ParserRegistry::empty();
+        registry.register(FabricParser);
+        registry.register(ForgeParser);
+        registry.register(NeoForgeParser);
+        registry
+    }
+}
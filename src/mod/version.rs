@@ -1,4 +1,5 @@
-use semver::{Version, VersionReq};
+use semver::{Comparator, Op, Version, VersionReq};
+use std::cmp::Ordering;
 use std::str::FromStr;
 use std::fmt;
 
@@ -21,6 +22,11 @@ impl FromStr for VersionConstraint {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let s = s.trim();
 
+        // Forge and NeoForge use an empty range to accept any version
+        if s.is_empty() {
+            return Ok(VersionConstraint::Semver(VersionReq::STAR));
+        }
+
         // Try parsing as a semver::VersionReq first
         if let Ok(req) = VersionReq::from_str(s) {
             return Ok(VersionConstraint::Semver(req));
@@ -49,13 +55,7 @@ fn parse_bound(s: &str, inclusive: bool) -> Result<VersionBound, String> {
     if s.is_empty() {
         Ok(VersionBound::Unbounded)
     } else {
-        let normalized = match s.split('.').count() {
-            1 => format!("{}.0.0", s),
-            2 => format!("{}.0", s),
-            _ => s.to_string(),
-        };
-        
-        let version = Version::parse(&normalized)
+        let version = Version::parse(&normalize_version(s))
             .map_err(|e| format!("Invalid version: {}", e))?;
 
         if inclusive {
@@ -66,6 +66,15 @@ fn parse_bound(s: &str, inclusive: bool) -> Result<VersionBound, String> {
     }
 }
 
+/// Pads versions without a patch or minor number, e.g. `1.21` to `1.21.0`.
+pub fn normalize_version(version: &str) -> String {
+    match version.split('.').count() {
+        1 => format!("{}.0.0", version),
+        2 => format!("{}.0", version),
+        _ => version.to_string(),
+    }
+}
+
 impl fmt::Display for VersionConstraint {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -106,6 +115,190 @@ impl VersionConstraint {
     }
 }
 
+/// The versions between two bounds.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Interval {
+    pub min: VersionBound,
+    pub max: VersionBound,
+}
+
+impl Interval {
+    pub fn any() -> Self {
+        Interval { min: VersionBound::Unbounded, max: VersionBound::Unbounded }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match (&self.min, &self.max) {
+            (VersionBound::Inclusive(min), VersionBound::Inclusive(max)) => min > max,
+            (VersionBound::Inclusive(min) | VersionBound::Exclusive(min), VersionBound::Inclusive(max) | VersionBound::Exclusive(max)) => min >= max,
+            _ => false,
+        }
+    }
+
+    pub fn contains(&self, version: &Version) -> bool {
+        !self.is_above(version) && !self.is_below(version)
+    }
+
+    /// Whether every version of the interval is newer than `version`.
+    pub fn is_above(&self, version: &Version) -> bool {
+        match &self.min {
+            VersionBound::Inclusive(min) => version < min,
+            VersionBound::Exclusive(min) => version <= min,
+            VersionBound::Unbounded => false,
+        }
+    }
+
+    /// Whether every version of the interval is older than `version`.
+    pub fn is_below(&self, version: &Version) -> bool {
+        match &self.max {
+            VersionBound::Inclusive(max) => version > max,
+            VersionBound::Exclusive(max) => version >= max,
+            VersionBound::Unbounded => false,
+        }
+    }
+
+    pub fn intersect(&self, other: &Interval) -> Interval {
+        let min = match (&self.min, &other.min) {
+            (VersionBound::Unbounded, bound) | (bound, VersionBound::Unbounded) => bound.clone(),
+            (a, b) if bound_version(a) != bound_version(b) => {
+                if bound_version(a) > bound_version(b) { a.clone() } else { b.clone() }
+            }
+            (VersionBound::Exclusive(v), _) | (_, VersionBound::Exclusive(v)) => VersionBound::Exclusive(v.clone()),
+            (a, _) => a.clone(),
+        };
+        let max = match (&self.max, &other.max) {
+            (VersionBound::Unbounded, bound) | (bound, VersionBound::Unbounded) => bound.clone(),
+            (a, b) if bound_version(a) != bound_version(b) => {
+                if bound_version(a) < bound_version(b) { a.clone() } else { b.clone() }
+            }
+            (VersionBound::Exclusive(v), _) | (_, VersionBound::Exclusive(v)) => VersionBound::Exclusive(v.clone()),
+            (a, _) => a.clone(),
+        };
+        Interval { min, max }
+    }
+}
+
+fn bound_version(bound: &VersionBound) -> Option<&Version> {
+    match bound {
+        VersionBound::Inclusive(version) | VersionBound::Exclusive(version) => Some(version),
+        VersionBound::Unbounded => None,
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let min = match &self.min {
+            VersionBound::Inclusive(min) if self.max == VersionBound::Inclusive(min.clone()) => {
+                return write!(f, "={}", min);
+            }
+            VersionBound::Inclusive(min) => Some(format!(">={}", min)),
+            VersionBound::Exclusive(min) => Some(format!(">{}", min)),
+            VersionBound::Unbounded => None,
+        };
+        let max = match &self.max {
+            VersionBound::Inclusive(max) => Some(format!("<={}", max)),
+            VersionBound::Exclusive(max) => Some(format!("<{}", max)),
+            VersionBound::Unbounded => None,
+        };
+        match (min, max) {
+            (Some(min), Some(max)) => write!(f, "{}, {}", min, max),
+            (Some(bound), None) | (None, Some(bound)) => write!(f, "{}", bound),
+            (None, None) => write!(f, "*"),
+        }
+    }
+}
+
+impl VersionConstraint {
+    /// The versions the constraint matches, ignoring pre-release rules.
+    pub fn interval(&self) -> Interval {
+        match self {
+            VersionConstraint::Bracketed(min, max) => Interval { min: min.clone(), max: max.clone() },
+            VersionConstraint::Semver(req) => req.comparators.iter()
+                .map(comparator_interval)
+                .fold(Interval::any(), |interval, other| interval.intersect(&other)),
+        }
+    }
+}
+
+fn comparator_interval(comparator: &Comparator) -> Interval {
+    let Comparator { major, minor, patch, .. } = *comparator;
+    let mut version = Version::new(major, minor.unwrap_or(0), patch.unwrap_or(0));
+    version.pre = comparator.pre.clone();
+    // The first version past the precision the comparator was written with, e.g. `1.3.0` for `1.2`.
+    let next = match (minor, patch) {
+        (None, _) => Version::new(major + 1, 0, 0),
+        (Some(minor), None) => Version::new(major, minor + 1, 0),
+        (Some(minor), Some(patch)) => Version::new(major, minor, patch + 1),
+    };
+    let next_minor = Version::new(major, minor.unwrap_or(0) + 1, 0);
+    let next_major = Version::new(major + 1, 0, 0);
+
+    let (min, max) = match comparator.op {
+        Op::Exact | Op::Wildcard if patch.is_some() => (VersionBound::Inclusive(version.clone()), VersionBound::Inclusive(version)),
+        Op::Exact | Op::Wildcard => (VersionBound::Inclusive(version), VersionBound::Exclusive(next)),
+        Op::Greater if patch.is_some() => (VersionBound::Exclusive(version), VersionBound::Unbounded),
+        Op::Greater => (VersionBound::Inclusive(next), VersionBound::Unbounded),
+        Op::GreaterEq => (VersionBound::Inclusive(version), VersionBound::Unbounded),
+        Op::Less => (VersionBound::Unbounded, VersionBound::Exclusive(version)),
+        Op::LessEq if patch.is_some() => (VersionBound::Unbounded, VersionBound::Inclusive(version)),
+        Op::LessEq => (VersionBound::Unbounded, VersionBound::Exclusive(next)),
+        Op::Tilde if minor.is_some() => (VersionBound::Inclusive(version), VersionBound::Exclusive(next_minor)),
+        Op::Tilde => (VersionBound::Inclusive(version), VersionBound::Exclusive(next_major)),
+        Op::Caret => {
+            let max = match (major, minor, patch) {
+                (0, Some(0), Some(patch)) => Version::new(0, 0, patch + 1),
+                (0, Some(_), _) => next_minor,
+                _ => next_major,
+            };
+            (VersionBound::Inclusive(version), VersionBound::Exclusive(max))
+        }
+        _ => (VersionBound::Unbounded, VersionBound::Unbounded),
+    };
+    Interval { min, max }
+}
+
+/// The versions both unions of intervals contain.
+pub fn intersect_intervals(a: &[Interval], b: &[Interval]) -> Vec<Interval> {
+    a.iter()
+        .flat_map(|a| b.iter().map(move |b| a.intersect(b)))
+        .filter(|interval| !interval.is_empty())
+        .collect()
+}
+
+/// Compares two mod version strings, including ones that are not valid semver
+/// (e.g. `1.0.0.0` or `1.20.1-2.3`).
+///
+/// Versions are split into numeric and alphabetic parts on `.`, `-`, `+` and `_`.
+/// Numeric parts compare numerically, other parts lexically, and a missing part
+/// is older than any present one.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    if let (Ok(a), Ok(b)) = (Version::parse(a), Version::parse(b)) {
+        return a.cmp(&b);
+    }
+
+    let split = |s: &str| -> Vec<String> {
+        s.split(['.', '-', '+', '_'])
+            .filter(|part| !part.is_empty())
+            .map(|part| part.to_lowercase())
+            .collect()
+    };
+    let (a_parts, b_parts) = (split(a), split(b));
+
+    for (a_part, b_part) in a_parts.iter().zip(b_parts.iter()) {
+        let ordering = match (a_part.parse::<u64>(), b_part.parse::<u64>()) {
+            (Ok(a_num), Ok(b_num)) => a_num.cmp(&b_num),
+            (Ok(_), Err(_)) => Ordering::Greater,
+            (Err(_), Ok(_)) => Ordering::Less,
+            (Err(_), Err(_)) => a_part.cmp(b_part),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    a_parts.len().cmp(&b_parts.len())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -202,6 +395,13 @@ mod tests {
         assert!(!constraint.matches(&Version::parse("2.0.0").unwrap()));
     }
 
+    #[test]
+    fn test_parse_version_constraint_empty_matches_any() {
+        let constraint: VersionConstraint = "".parse().unwrap();
+        assert!(constraint.matches(&Version::parse("0.0.1").unwrap()));
+        assert!(constraint.matches(&Version::parse("21.1.77").unwrap()));
+    }
+
     #[test]
     fn test_parse_version_constraint_invalid_format() {
         let result: Result<VersionConstraint, _> = "invalid-version".parse();
@@ -226,4 +426,33 @@ mod tests {
         assert!(parse_bound("1.2.3.4", true).is_err());
         assert!(parse_bound("", true).is_ok());
     }
+
+    #[test]
+    fn test_intervals() {
+        let interval = |s: &str| s.parse::<VersionConstraint>().unwrap().interval();
+        let version = |s: &str| Version::parse(s).unwrap();
+
+        assert_eq!(interval("^1.2").to_string(), ">=1.2.0, <2.0.0");
+        assert_eq!(interval("~0.4.1").to_string(), ">=0.4.1, <0.5.0");
+        assert_eq!(interval("1.20.x").to_string(), ">=1.20.0, <1.21.0");
+        assert_eq!(interval(">1.2").to_string(), ">=1.3.0");
+        assert_eq!(interval("[1.0,2.0)").intersect(&interval(">=1.5.0, <3")).to_string(), ">=1.5.0, <2.0.0");
+        assert_eq!(interval("[1.0,2.0]").intersect(&interval(">=2.0.0")).to_string(), "=2.0.0");
+        assert!(interval("<1.0.0").intersect(&interval(">=1.0.0")).is_empty());
+        assert!(interval(">=1.5").is_above(&version("1.4.9")));
+        assert!(interval("<1.5").is_below(&version("1.5.0")));
+
+        let union = vec![interval("<1.0.0"), interval(">=2.0.0")];
+        assert!(intersect_intervals(&union, &[interval("^1.9")]).is_empty());
+        assert_eq!(intersect_intervals(&union, &[interval(">=0.5")]).len(), 2);
+    }
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions("1.2.0", "1.10.0"), Ordering::Less);
+        assert_eq!(compare_versions("1.0.0.1", "1.0.0.0"), Ordering::Greater);
+        assert_eq!(compare_versions("1.20.1-2.3", "1.20.1-2.3"), Ordering::Equal);
+        assert_eq!(compare_versions("2.0", "2.0.1"), Ordering::Less);
+        assert_eq!(compare_versions("1.0.0-beta.2", "1.0.0"), Ordering::Less);
+    }
 }
@@ -0,0 +1,308 @@
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use mmod::hash::{hash_bytes, HashFormat};
+use mmod::{compare_versions, ModFile, Platform};
+
+// https://docs.modrinth.com/api/
+pub const DEFAULT_BASE_URL: &str = "https://api.modrinth.com/v2";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Project {
+    pub id: String,
+    pub slug: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Version {
+    pub id: String,
+    pub project_id: String,
+    pub version_number: String,
+    #[serde(default)]
+    pub game_versions: Vec<String>,
+    pub files: Vec<VersionFile>,
+}
+
+impl Version {
+    /// The file to install, i.e. the primary one or else the first.
+    pub fn primary_file(&self) -> Option<&VersionFile> {
+        self.files.iter().find(|file| file.primary).or(self.files.first())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct VersionFile {
+    /// The file's hashes, keyed by algorithm (`sha1` and `sha512`).
+    pub hashes: HashMap<String, String>,
+    pub url: String,
+    pub filename: String,
+    #[serde(default)]
+    pub primary: bool,
+}
+
+/// The project and version a missing dependency can be installed from.
+pub struct Resolution {
+    pub dependency_id: String,
+    pub project: Project,
+    pub version: Version,
+}
+
+pub struct ModrinthClient {
+    agent: ureq::Agent,
+    base_url: String,
+}
+
+impl ModrinthClient {
+    /// Creates a client of the API at `base_url`, e.g. `DEFAULT_BASE_URL`.
+    pub fn new(base_url: &str) -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .user_agent(concat!("XiNoYv/mmod/", env!("CARGO_PKG_VERSION")))
+            .build();
+        ModrinthClient { agent, base_url: base_url.trim_end_matches('/').to_string() }
+    }
+
+    /// Looks up the versions files with these sha1 hashes belong to, keyed by hash.
+    /// Files Modrinth doesn't know are left out.
+    pub fn versions_from_hashes(&self, sha1s: &[&str]) -> Result<HashMap<String, Version>> {
+        let url = format!("{}/version_files", self.base_url);
+        let response = self.agent.post(&url)
+            .send_json(serde_json::json!({ "hashes": sha1s, "algorithm": "sha1" }))
+            .with_context(|| format!("Failed to query {}", url))?;
+        Ok(response.into_json()?)
+    }
+
+    /// Looks up a project by id or slug.
+    pub fn project(&self, id_or_slug: &str) -> Result<Option<Project>> {
+        let url = format!("{}/project/{}", self.base_url, id_or_slug);
+        match self.agent.get(&url).call() {
+            Ok(response) => Ok(Some(response.into_json()?)),
+            Err(ureq::Error::Status(404, _)) => Ok(None),
+            Err(e) => Err(e).with_context(|| format!("Failed to query {}", url)),
+        }
+    }
+
+    /// The versions of a project for `loader` and, when given, `game_version`,
+    /// newest first.
+    pub fn compatible_versions(&self, project_id: &str, loader: &Platform, game_version: Option<&str>) -> Result<Vec<Version>> {
+        let url = format!("{}/project/{}/version", self.base_url, project_id);
+        let mut request = self.agent.get(&url)
+            .query("loaders", &serde_json::json!([loader_name(loader)]).to_string());
+        if let Some(game_version) = game_version {
+            request = request.query("game_versions", &serde_json::json!([game_version]).to_string());
+        }
+        let response = request.call().with_context(|| format!("Failed to query {}", url))?;
+        Ok(response.into_json()?)
+    }
+
+    /// Downloads `file` into `dir`, refusing it if its contents don't match
+    /// the hashes Modrinth published for it, or if there are none.
+    pub fn download(&self, file: &VersionFile, dir: &Path) -> Result<PathBuf> {
+        let response = self.agent.get(&file.url).call()
+            .with_context(|| format!("Failed to download {}", file.url))?;
+        let mut bytes = Vec::new();
+        response.into_reader().read_to_end(&mut bytes)?;
+
+        let mut verified = false;
+        for (algorithm, format) in [("sha512", HashFormat::Sha512), ("sha1", HashFormat::Sha1)] {
+            if let Some(expected) = file.hashes.get(algorithm) {
+                if !hash_bytes(&bytes, format).eq_ignore_ascii_case(expected) {
+                    anyhow::bail!("{} doesn't match its {} hash, not saved", file.filename, algorithm);
+                }
+                verified = true;
+            }
+        }
+        if !verified {
+            anyhow::bail!("Modrinth published no sha512 or sha1 hash for {}, not saved", file.filename);
+        }
+
+        // Don't let the API pick a path outside of `dir`.
+        let file_name = Path::new(&file.filename).file_name()
+            .with_context(|| format!("Invalid file name {}", file.filename))?;
+        let path = dir.join(file_name);
+        if path.exists() {
+            anyhow::bail!("{} already exists", path.display());
+        }
+        std::fs::write(&path, bytes).with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+fn loader_name(loader: &Platform) -> &str {
+    match loader {
+        Platform::Forge => "forge",
+        Platform::Fabric => "fabric",
+        Platform::NeoForge => "neoforge",
+        Platform::Quilt => "quilt",
+        Platform::Unknown(name) => name,
+    }
+}
+
+/// Identifies the jars of `files` on Modrinth by their sha1, keyed by file name.
+pub fn identify(client: &ModrinthClient, files: &HashMap<String, ModFile>) -> Result<HashMap<String, Version>> {
+    let sha1s: Vec<(&String, &str)> = files.iter()
+        .map(|(file_name, file)| Ok((file_name, file.hashes()?.sha1.as_str())))
+        .collect::<mmod::Result<_>>()?;
+    let mut versions = client.versions_from_hashes(&sha1s.iter().map(|(_, sha1)| *sha1).collect::<Vec<_>>())?;
+
+    Ok(sha1s.into_iter()
+        .filter_map(|(file_name, sha1)| {
+            let version = versions.remove(sha1)?;
+            Some((file_name.clone(), version))
+        })
+        .collect())
+}
+
+/// The newest game version every one of `versions` supports.
+pub fn common_game_version<'a>(versions: impl IntoIterator<Item = &'a Version>) -> Option<String> {
+    let mut versions = versions.into_iter();
+    let mut common = versions.next()?.game_versions.clone();
+    for version in versions {
+        common.retain(|game_version| version.game_versions.contains(game_version));
+    }
+    common.into_iter().max_by(|a, b| compare_versions(a, b))
+}
+
+/// Finds, for each of `dependency_ids`, a project whose id or slug matches it
+/// and its newest version for `loader` and `game_version`.
+///
+/// Ids that match no project, or no compatible version, are left out.
+pub fn resolve_missing(
+    client: &ModrinthClient,
+    dependency_ids: &[String],
+    loader: &Platform,
+    game_version: Option<&str>,
+) -> Result<Vec<Resolution>> {
+    let mut resolutions = Vec::new();
+    for dependency_id in dependency_ids {
+        // Mod ids can't contain dashes while slugs usually do, e.g. `cloth_config` is `cloth-config`.
+        let mut candidates = vec![dependency_id.clone()];
+        if dependency_id.contains('_') {
+            candidates.push(dependency_id.replace('_', "-"));
+        }
+
+        for candidate in candidates {
+            let Some(project) = client.project(&candidate)? else { continue };
+            let versions = client.compatible_versions(&project.id, loader, game_version)?;
+            if let Some(version) = versions.into_iter().next() {
+                resolutions.push(Resolution { dependency_id: dependency_id.clone(), project, version });
+                break;
+            }
+        }
+    }
+    Ok(resolutions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    /// Serves the routes built by `routes` from the server's base URL, as
+    /// `(method and path prefix, body)`, answering 404 to anything else.
+    /// Returns the base URL.
+    fn mock_server(routes: impl FnOnce(&str) -> Vec<(String, Vec<u8>)>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let routes = routes(&base_url);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut content_length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some(length) = header.to_lowercase().strip_prefix("content-length:") {
+                        content_length = length.trim().parse().unwrap();
+                    }
+                }
+                reader.read_exact(&mut vec![0; content_length]).unwrap();
+
+                let target: String = request_line.split(' ').take(2).collect::<Vec<_>>().join(" ");
+                let (status, body) = match routes.iter().find(|(route, _)| target.starts_with(route.as_str())) {
+                    Some((_, body)) => ("200 OK", body.clone()),
+                    None => ("404 Not Found", b"{}".to_vec()),
+                };
+                write!(stream, "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", status, body.len()).unwrap();
+                stream.write_all(&body).unwrap();
+            }
+        });
+        base_url
+    }
+
+    fn version_json(base_url: &str, project_id: &str, contents: &[u8], game_versions: &[&str]) -> serde_json::Value {
+        serde_json::json!({
+            "id": format!("{}-version", project_id),
+            "project_id": project_id,
+            "version_number": "1.0.0",
+            "game_versions": game_versions,
+            "loaders": ["fabric"],
+            "files": [{
+                "hashes": {
+                    "sha1": hash_bytes(contents, HashFormat::Sha1),
+                    "sha512": hash_bytes(contents, HashFormat::Sha512),
+                },
+                "url": format!("{}/download/{}.jar", base_url, project_id),
+                "filename": format!("{}.jar", project_id),
+                "primary": true,
+            }],
+        })
+    }
+
+    #[test]
+    fn test_identify_and_resolve_missing() {
+        let installed = b"installed jar".to_vec();
+        let dependency = b"dependency jar".to_vec();
+        let base_url = mock_server(|base_url| vec![
+            ("POST /version_files".to_string(), serde_json::json!({
+                hash_bytes(&installed, HashFormat::Sha1): version_json(base_url, "installed", &installed, &["1.20.1", "1.20.2"]),
+            }).to_string().into_bytes()),
+            ("GET /project/cloth/version".to_string(), serde_json::json!([
+                version_json(base_url, "cloth", &dependency, &["1.20.1"]),
+            ]).to_string().into_bytes()),
+            // A project matching the mod id, but with no compatible version
+            ("GET /project/abandoned/version".to_string(), b"[]".to_vec()),
+            ("GET /project/cloth_config".to_string(), serde_json::json!({
+                "id": "abandoned", "slug": "cloth_config", "title": "Abandoned",
+            }).to_string().into_bytes()),
+            ("GET /project/cloth-config".to_string(), serde_json::json!({
+                "id": "cloth", "slug": "cloth-config", "title": "Cloth Config API",
+            }).to_string().into_bytes()),
+            ("GET /download/cloth.jar".to_string(), dependency.clone()),
+        ]);
+        let client = ModrinthClient::new(&base_url);
+
+        let files = HashMap::from([
+            ("installed.jar".to_string(), ModFile::of(None, &installed)),
+            ("unknown.jar".to_string(), ModFile::of(None, b"unknown")),
+        ]);
+        let identified = identify(&client, &files).unwrap();
+        assert_eq!(identified.keys().collect::<Vec<_>>(), vec!["installed.jar"]);
+        assert_eq!(common_game_version(identified.values()), Some("1.20.2".to_string()));
+
+        let dependency_ids = vec!["cloth_config".to_string(), "nowhere".to_string()];
+        let resolutions = resolve_missing(&client, &dependency_ids, &Platform::Fabric, Some("1.20.1")).unwrap();
+        assert_eq!(resolutions.len(), 1);
+        assert_eq!(resolutions[0].project.slug, "cloth-config");
+
+        let dir = std::env::temp_dir().join(format!("mmod-modrinth-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = client.download(resolutions[0].version.primary_file().unwrap(), &dir).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), dependency);
+
+        let unhashed = VersionFile { hashes: HashMap::new(), ..resolutions[0].version.primary_file().unwrap().clone() };
+        std::fs::remove_file(&path).unwrap();
+        assert!(client.download(&unhashed, &dir).is_err());
+        assert!(!path.exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
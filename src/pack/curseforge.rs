@@ -0,0 +1,239 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Seek};
+use std::path::Path;
+use serde::Deserialize;
+use zip::ZipArchive;
+use crate::error::{Error, Result};
+use crate::hash::curseforge_fingerprint;
+use crate::jar::{self, Archive};
+use crate::pack::{Origin, Pack};
+use crate::r#mod::{Environment, Platform};
+use crate::scan::{ModFile, ScanOptions};
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+pub const INSTANCE_FILE: &str = "minecraftinstance.json";
+
+/// The `manifest.json` of a modpack exported by CurseForge.
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    minecraft: ManifestMinecraft,
+    files: Vec<ManifestFile>,
+    /// The directory of the zip whose contents are copied into the instance.
+    #[serde(default = "default_overrides")]
+    overrides: String,
+}
+
+fn default_overrides() -> String {
+    "overrides".to_string()
+}
+
+#[derive(Debug, Deserialize)]
+struct ManifestMinecraft {
+    version: String,
+    #[serde(rename = "modLoaders", default)]
+    mod_loaders: Vec<ManifestModLoader>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ManifestModLoader {
+    /// The loader and its version, e.g. `forge-47.2.0`.
+    id: String,
+    #[serde(default)]
+    primary: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct ManifestFile {
+    #[serde(rename = "projectID")]
+    project_id: u64,
+    #[serde(rename = "fileID")]
+    file_id: u64,
+}
+
+/// The `minecraftinstance.json` the CurseForge app keeps in each instance directory.
+#[derive(Debug, Deserialize)]
+struct Instance {
+    #[serde(rename = "gameVersion")]
+    game_version: Option<String>,
+    #[serde(rename = "baseModLoader")]
+    base_mod_loader: Option<InstanceModLoader>,
+    #[serde(rename = "installedAddons", default)]
+    installed_addons: Vec<InstalledAddon>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InstanceModLoader {
+    /// The loader and its version, e.g. `forge-47.2.0`.
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct InstalledAddon {
+    #[serde(rename = "addonID")]
+    addon_id: u64,
+    #[serde(rename = "installedFile")]
+    installed_file: Option<InstalledFile>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InstalledFile {
+    id: u64,
+    #[serde(rename = "fileName")]
+    file_name: String,
+    #[serde(rename = "fileFingerprint")]
+    file_fingerprint: Option<u32>,
+}
+
+/// Whether `path` is a zip exported by CurseForge, as opposed to a `.mrpack`.
+pub fn is_modpack_zip(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "zip")
+        && jar::open_jar_file(path).is_ok_and(|mut archive| archive.by_name(MANIFEST_FILE).is_ok())
+}
+
+pub fn read_modpack_zip(path: &Path, options: &ScanOptions) -> Result<Pack> {
+    let mut archive = jar::open_jar_file(path)?;
+    read_modpack_archive(&mut archive, options)
+}
+
+fn read_modpack_archive<R: Read + Seek>(archive: &mut ZipArchive<R>, options: &ScanOptions) -> Result<Pack> {
+    let contents = jar::read_entry(archive, MANIFEST_FILE, MANIFEST_FILE)?;
+    let manifest: Manifest = serde_json::from_str(&contents)
+        .map_err(|e| Error::metadata("the modpack", MANIFEST_FILE, e))?;
+
+    let overrides = format!("{}/", manifest.overrides.trim_end_matches('/'));
+    let mut mods = Vec::new();
+    let mut files = HashMap::new();
+    let mut skipped = Vec::new();
+    let mut warnings = Vec::new();
+    let entries: Vec<String> = archive.file_names().map(String::from).collect();
+    for entry in entries {
+        let Some(instance_path) = entry.strip_prefix(&overrides) else { continue };
+        if !instance_path.ends_with(".jar") || !options.accepts(instance_path) {
+            continue;
+        }
+
+        let bytes = archive.read(&entry)
+            .map_err(|e| Error::archive(&entry, e))?;
+        match options.parsers.parse_bytes(&bytes, &entry) {
+            Ok(parsed) => {
+                mods.extend(parsed);
+                // The manifest has no fingerprints to match bundled jars against its files.
+                warnings.push(format!("{} is bundled in the pack, its CurseForge project and file are unknown", entry));
+                files.insert(entry, ModFile::of(None, &bytes));
+            }
+            Err(e) => skipped.push((entry, e)),
+        }
+    }
+
+    // The manifest only lists ids, which can't be matched to mods without the CurseForge API.
+    let unavailable = manifest.files.iter()
+        .map(|file| Origin::CurseForge { project_id: file.project_id, file_id: file.file_id }.to_string())
+        .collect();
+
+    let loader = manifest.minecraft.mod_loaders.iter()
+        .find(|loader| loader.primary)
+        .or(manifest.minecraft.mod_loaders.first())
+        .and_then(|loader| parse_loader(&loader.id));
+
+    Ok(Pack {
+        mods,
+        files,
+        mods_dir: None,
+        environment: Environment { minecraft: Some(manifest.minecraft.version), loader },
+        unavailable,
+        origins: HashMap::new(),
+        warnings,
+        skipped,
+    })
+}
+
+/// Reads the instance described by a `minecraftinstance.json`, whose jars are
+/// installed in the `mods` directory next to it.
+pub fn read_instance(path: &Path) -> Result<Pack> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| Error::io("read", path, e))?;
+    let instance: Instance = serde_json::from_str(&contents)
+        .map_err(|e| Error::metadata("the instance", INSTANCE_FILE, e))?;
+
+    let mods_dir = path.parent().unwrap_or(Path::new(".")).join("mods");
+    let installed: HashSet<String> = std::fs::read_dir(&mods_dir)
+        .map(|entries| entries.flatten().map(|e| e.file_name().to_string_lossy().into_owned()).collect())
+        .unwrap_or_default();
+
+    let mut origins = HashMap::new();
+    let mut unavailable = Vec::new();
+    let mut warnings = Vec::new();
+    for addon in instance.installed_addons {
+        let Some(file) = addon.installed_file else { continue };
+        if !file.file_name.ends_with(".jar") {
+            continue;
+        }
+        if installed.contains(&file.file_name) {
+            let path = mods_dir.join(&file.file_name);
+            let bytes = std::fs::read(&path)
+                .map_err(|e| Error::io("read", &path, e))?;
+            if file.file_fingerprint.is_some_and(|expected| expected != curseforge_fingerprint(&bytes)) {
+                warnings.push(format!("{} doesn't match its CurseForge fingerprint in {}", file.file_name, INSTANCE_FILE));
+            }
+        } else if !installed.contains(&format!("{}.disabled", file.file_name)) {
+            unavailable.push(file.file_name.clone());
+        }
+        origins.insert(file.file_name, Origin::CurseForge { project_id: addon.addon_id, file_id: file.id });
+    }
+
+    Ok(Pack {
+        mods: Vec::new(),
+        files: HashMap::new(),
+        mods_dir: Some(mods_dir),
+        environment: Environment {
+            minecraft: instance.game_version,
+            loader: instance.base_mod_loader.and_then(|loader| parse_loader(&loader.name)),
+        },
+        unavailable,
+        origins,
+        warnings,
+        skipped: Vec::new(),
+    })
+}
+
+/// Parses a CurseForge loader id such as `forge-47.2.0` or `fabric-0.15.11`.
+fn parse_loader(id: &str) -> Option<(Platform, String)> {
+    let (name, version) = id.split_once('-')?;
+    Some((name.parse().ok()?, version.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use crate::jar::test_util::zip;
+
+    #[test]
+    fn test_read_modpack_archive() {
+        let manifest = r#"{
+            "minecraft": {"version": "1.20.1", "modLoaders": [{"id": "forge-47.2.0", "primary": true}]},
+            "manifestType": "minecraftModpack",
+            "manifestVersion": 1,
+            "name": "Example Pack",
+            "files": [{"projectID": 238222, "fileID": 4712345, "required": true}],
+            "overrides": "overrides"
+        }"#;
+        let bundled_jar = zip(&[("fabric.mod.json", br#"{"schemaVersion": 1, "id": "bundled", "version": "1.0.0"}"#)]);
+        let pack = zip(&[(MANIFEST_FILE, manifest.as_bytes()), ("overrides/mods/bundled.jar", &bundled_jar)]);
+
+        let options = ScanOptions::new(false, &[], &[]).unwrap();
+        let pack = read_modpack_archive(&mut ZipArchive::new(Cursor::new(pack)).unwrap(), &options).unwrap();
+
+        let mods: Vec<_> = pack.mods.iter().map(|m| m.file_name.as_str()).collect();
+        assert_eq!(mods, vec!["overrides/mods/bundled.jar"]);
+        assert!(pack.origins.is_empty());
+        assert_eq!(pack.warnings, vec![
+            "overrides/mods/bundled.jar is bundled in the pack, its CurseForge project and file are unknown".to_string(),
+        ]);
+        assert_eq!(pack.unavailable, vec!["CurseForge project 238222, file 4712345".to_string()]);
+        assert_eq!(pack.environment, Environment {
+            minecraft: Some("1.20.1".to_string()),
+            loader: Some((Platform::Forge, "47.2.0".to_string())),
+        });
+    }
+}
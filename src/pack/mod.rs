@@ -0,0 +1,99 @@
+mod mrpack;
+mod curseforge;
+mod packwiz;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use crate::error::{Error, Result};
+use crate::r#mod::{Environment, ModMetadata};
+use crate::scan::{ModFile, ScanOptions};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// The `mods` directory and `index.toml` of a packwiz pack.
+    Packwiz,
+}
+
+/// The mods of a modpack, as far as they can be known without downloading anything.
+pub struct Pack {
+    /// The mods of the jars bundled in the pack, named by their path inside it.
+    pub mods: Vec<ModMetadata>,
+    /// The jars bundled in the pack, keyed like `mods`.
+    pub files: HashMap<String, ModFile>,
+    /// The directory the pack's jars are installed to, for packs describing an
+    /// installed instance. It is scanned like any mods directory.
+    pub mods_dir: Option<PathBuf>,
+    pub environment: Environment,
+    /// Jars the pack downloads at install time, by their path in the instance,
+    /// or by their id when the pack doesn't name them.
+    pub unavailable: Vec<String>,
+    /// Where the pack's jars come from, keyed by file name.
+    pub origins: HashMap<String, Origin>,
+    /// Inconsistencies between the pack and the jars, e.g. changed hashes.
+    pub warnings: Vec<String>,
+    /// Bundled jars that couldn't be parsed, with the reason, keyed like `mods`.
+    pub skipped: Vec<(String, Error)>,
+}
+
+/// The platform a jar was downloaded from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Origin {
+    CurseForge { project_id: u64, file_id: u64 },
+    Modrinth { project_id: String, version_id: String },
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Origin::CurseForge { project_id, file_id } => {
+                write!(f, "CurseForge project {}, file {}", project_id, file_id)
+            }
+            Origin::Modrinth { project_id, version_id } => {
+                write!(f, "Modrinth project {}, version {}", project_id, version_id)
+            }
+        }
+    }
+}
+
+/// Whether `path` is a modpack or an instance file rather than a mods directory.
+pub fn is_pack(path: &Path) -> bool {
+    path.is_file() && (
+        path.extension().is_some_and(|ext| ext == "mrpack")
+            || path.file_name().is_some_and(|name| name == curseforge::INSTANCE_FILE || name == packwiz::PACK_FILE)
+            || curseforge::is_modpack_zip(path)
+    )
+}
+
+pub fn read_pack(path: &Path, options: &ScanOptions) -> Result<Pack> {
+    if path.file_name().is_some_and(|name| name == curseforge::INSTANCE_FILE) {
+        curseforge::read_instance(path)
+    } else if path.file_name().is_some_and(|name| name == packwiz::PACK_FILE) {
+        packwiz::read_packwiz(path, options)
+    } else if path.extension().is_some_and(|ext| ext == "zip") {
+        curseforge::read_modpack_zip(path, options)
+    } else {
+        mrpack::read_mrpack(path, options)
+    }
+}
+
+/// The files written by an export.
+pub struct Exported {
+    pub written: Vec<PathBuf>,
+    /// Written files describing jars of unknown origin, whose download URL the
+    /// user has to fill in.
+    pub incomplete: Vec<PathBuf>,
+}
+
+/// Describes the jars of `mods` in `format` under `output`.
+pub fn export(
+    format: ExportFormat,
+    mods: &[ModMetadata],
+    files: &HashMap<String, ModFile>,
+    origins: &HashMap<String, Origin>,
+    output: &Path,
+) -> Result<Exported> {
+    match format {
+        ExportFormat::Packwiz => packwiz::export_packwiz(mods, files, origins, output),
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Seek};
+use std::path::Path;
+use serde::Deserialize;
+use zip::ZipArchive;
+use crate::error::{Error, Result};
+use crate::hash::FileHashes;
+use crate::jar::{self, Archive};
+use crate::pack::Pack;
+use crate::r#mod::{Environment, Platform, Side};
+use crate::scan::{ModFile, ScanOptions};
+
+// https://support.modrinth.com/en/articles/8802351-modrinth-modpack-format-mrpack
+const INDEX_FILE: &str = "modrinth.index.json";
+const OVERRIDE_DIRS: [&str; 3] = ["overrides/", "client-overrides/", "server-overrides/"];
+
+#[derive(Debug, Deserialize)]
+struct ModrinthIndex {
+    files: Vec<IndexFile>,
+    /// The game and loader versions, keyed by `minecraft`, `forge`, `neoforge`,
+    /// `fabric-loader` or `quilt-loader`.
+    dependencies: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IndexFile {
+    /// Where the file is installed, relative to the instance directory.
+    path: String,
+    /// The file's hashes, keyed by `sha1` or `sha512`.
+    hashes: HashMap<String, String>,
+    env: Option<Env>,
+}
+
+/// Whether a file is `required`, `optional` or `unsupported` on each side.
+#[derive(Debug, Deserialize)]
+struct Env {
+    client: String,
+    server: String,
+}
+
+impl Env {
+    /// The only side the file is installed on, if it isn't installed on both.
+    fn side(&self) -> Option<Side> {
+        match (self.client.as_str(), self.server.as_str()) {
+            ("unsupported", "unsupported") => None,
+            ("unsupported", _) => Some(Side::Server),
+            (_, "unsupported") => Some(Side::Client),
+            _ => None,
+        }
+    }
+}
+
+/// Reads the `.mrpack` at `path`. The jars it bundles in its overrides are
+/// parsed, those it downloads are reported as unavailable.
+///
+/// A bundled jar also listed in the index takes the side of its `env` there,
+/// and is checked against its hashes there.
+pub fn read_mrpack(path: &Path, options: &ScanOptions) -> Result<Pack> {
+    let mut archive = jar::open_jar_file(path)?;
+    read_mrpack_archive(&mut archive, options)
+}
+
+fn read_mrpack_archive<R: Read + Seek>(archive: &mut ZipArchive<R>, options: &ScanOptions) -> Result<Pack> {
+    let contents = jar::read_entry(archive, INDEX_FILE, INDEX_FILE)?;
+    let index: ModrinthIndex = serde_json::from_str(&contents)
+        .map_err(|e| Error::metadata("the modpack", INDEX_FILE, e))?;
+
+    let listed: HashMap<&str, &IndexFile> = index.files.iter().map(|file| (file.path.as_str(), file)).collect();
+    let mut mods = Vec::new();
+    let mut files = HashMap::new();
+    let mut bundled = HashSet::new();
+    let mut warnings = Vec::new();
+    let mut skipped = Vec::new();
+    let entries: Vec<String> = archive.file_names().map(String::from).collect();
+    for entry in entries {
+        let Some(instance_path) = OVERRIDE_DIRS.iter().find_map(|dir| entry.strip_prefix(dir)) else { continue };
+        if !instance_path.ends_with(".jar") || !options.accepts(instance_path) {
+            continue;
+        }
+        bundled.insert(instance_path.to_string());
+
+        let bytes = archive.read(&entry)
+            .map_err(|e| Error::archive(&entry, e))?;
+        let listed = listed.get(instance_path);
+        if let Some(file) = listed {
+            check_hashes(&entry, &bytes, &file.hashes, &mut warnings);
+        }
+        match options.parsers.parse_bytes(&bytes, &entry) {
+            Ok(mut parsed) => {
+                if let Some(side) = listed.and_then(|file| file.env.as_ref()?.side()) {
+                    parsed.iter_mut().for_each(|mod_| mod_.side = side);
+                }
+                mods.extend(parsed);
+                files.insert(entry, ModFile::of(None, &bytes));
+            }
+            Err(e) => skipped.push((entry, e)),
+        }
+    }
+
+    let unavailable = index.files.iter()
+        .map(|file| file.path.clone())
+        .filter(|path| path.ends_with(".jar") && options.accepts(path) && !bundled.contains(path))
+        .collect();
+
+    Ok(Pack {
+        mods,
+        files,
+        mods_dir: None,
+        environment: environment(&index.dependencies),
+        unavailable,
+        origins: HashMap::new(),
+        warnings,
+        skipped,
+    })
+}
+
+/// Compares the bundled jar `entry` with the `hashes` the index lists for it.
+fn check_hashes(entry: &str, bytes: &[u8], hashes: &HashMap<String, String>, warnings: &mut Vec<String>) {
+    let actual = FileHashes::of(bytes);
+    let mismatch = [("sha512", &actual.sha512), ("sha1", &actual.sha1)]
+        .into_iter()
+        .find(|(format, hash)| hashes.get(*format).is_some_and(|expected| !expected.eq_ignore_ascii_case(hash)));
+    if let Some((format, _)) = mismatch {
+        warnings.push(format!("{} doesn't match its {} hash in {}", entry, format, INDEX_FILE));
+    }
+}
+
+fn environment(dependencies: &HashMap<String, String>) -> Environment {
+    let loader = [
+        ("fabric-loader", Platform::Fabric),
+        ("quilt-loader", Platform::Quilt),
+        ("forge", Platform::Forge),
+        ("neoforge", Platform::NeoForge),
+    ]
+        .into_iter()
+        .find_map(|(key, platform)| dependencies.get(key).map(|version| (platform, version.clone())));
+
+    Environment { minecraft: dependencies.get("minecraft").cloned(), loader }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use crate::jar::test_util::zip;
+
+    #[test]
+    fn test_read_mrpack_archive() {
+        let index = r#"{
+            "formatVersion": 1,
+            "game": "minecraft",
+            "versionId": "1.0.0",
+            "name": "Example Pack",
+            "files": [
+                {"path": "mods/sodium.jar", "hashes": {}, "downloads": [], "fileSize": 1},
+                {"path": "mods/bundled.jar", "hashes": {"sha1": "00"}, "downloads": [], "fileSize": 1,
+                 "env": {"client": "required", "server": "unsupported"}},
+                {"path": "resourcepacks/faithful.zip", "hashes": {}, "downloads": [], "fileSize": 1}
+            ],
+            "dependencies": {"minecraft": "1.20.1", "fabric-loader": "0.15.11"}
+        }"#;
+        let bundled_jar = zip(&[("fabric.mod.json", br#"{"schemaVersion": 1, "id": "bundled", "version": "1.0.0"}"#)]);
+        let pack = zip(&[
+            (INDEX_FILE, index.as_bytes()),
+            ("overrides/mods/bundled.jar", &bundled_jar),
+            ("overrides/config/bundled.toml", b""),
+        ]);
+
+        let options = ScanOptions::new(false, &[], &[]).unwrap();
+        let pack = read_mrpack_archive(&mut ZipArchive::new(Cursor::new(pack)).unwrap(), &options).unwrap();
+
+        let mods: Vec<_> = pack.mods.iter().map(|m| (m.mod_id.as_str(), m.file_name.as_str())).collect();
+        assert_eq!(mods, vec![("bundled", "overrides/mods/bundled.jar")]);
+        assert_eq!(pack.mods[0].side, Side::Client);
+        assert_eq!(pack.warnings, vec![format!("overrides/mods/bundled.jar doesn't match its sha1 hash in {}", INDEX_FILE)]);
+        assert_eq!(pack.unavailable, vec!["mods/sodium.jar".to_string()]);
+        assert_eq!(pack.environment, Environment {
+            minecraft: Some("1.20.1".to_string()),
+            loader: Some((Platform::Fabric, "0.15.11".to_string())),
+        });
+    }
+}
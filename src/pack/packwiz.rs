@@ -0,0 +1,608 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::error::{Error, Result};
+use crate::hash::{hash_bytes, hash_file, FileHashes, HashFormat};
+use crate::pack::{Exported, Origin, Pack};
+use crate::r#mod::{Environment, ModMetadata, Platform, Side};
+use crate::scan::{ModFile, ScanOptions};
+
+// https://packwiz.infra.link/reference/pack-format/
+pub const PACK_FILE: &str = "pack.toml";
+const MODS_DIR: &str = "mods";
+
+#[derive(Debug, Deserialize)]
+struct PackToml {
+    index: IndexRef,
+    /// The game and loader versions, keyed by `minecraft`, `forge`, `neoforge`,
+    /// `fabric` or `quilt`.
+    versions: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IndexRef {
+    file: String,
+    #[serde(rename = "hash-format")]
+    hash_format: String,
+    hash: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct IndexToml {
+    #[serde(rename = "hash-format")]
+    hash_format: String,
+    #[serde(default)]
+    files: Vec<IndexEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct IndexEntry {
+    /// The path of the file, relative to the pack root.
+    file: String,
+    hash: String,
+    #[serde(rename = "hash-format", skip_serializing_if = "Option::is_none")]
+    hash_format: Option<String>,
+    /// Whether the file is a `.pw.toml` describing a file to download.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    metafile: bool,
+}
+
+/// A `.pw.toml` metadata file.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+struct MetaFile {
+    name: String,
+    filename: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    side: Option<String>,
+    download: Download,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    update: Option<Update>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+struct Download {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mode: Option<String>,
+    hash_format: String,
+    hash: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Update {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    curseforge: Option<CurseForgeUpdate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    modrinth: Option<ModrinthUpdate>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+struct CurseForgeUpdate {
+    project_id: u64,
+    file_id: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+struct ModrinthUpdate {
+    mod_id: String,
+    version: String,
+}
+
+/// Reads the packwiz pack described by `path`, a `pack.toml`, whose jars are
+/// expected in the `mods` directory next to it.
+///
+/// The hashes of `index.toml` and of the `.pw.toml` files are checked against
+/// the jars that are present.
+pub fn read_packwiz(path: &Path, options: &ScanOptions) -> Result<Pack> {
+    let pack: PackToml = read_toml(path)?;
+    let root = path.parent().unwrap_or(Path::new("."));
+    let mods_dir = root.join(MODS_DIR);
+
+    let mut warnings = Vec::new();
+    let index_path = root.join(&pack.index.file);
+    check_hash(&index_path, &pack.index.file, &pack.index.hash_format, &pack.index.hash, PACK_FILE, &mut warnings)?;
+    let index: IndexToml = read_toml(&index_path)?;
+
+    let mut origins = HashMap::new();
+    let mut unavailable = Vec::new();
+    let mut listed = HashSet::new();
+    for entry in &index.files {
+        let (jar_path, hash_format, hash, origin) = if entry.metafile {
+            let meta: MetaFile = read_toml(&root.join(&entry.file))?;
+            let jar_path = match entry.file.rsplit_once('/') {
+                Some((dir, _)) => format!("{}/{}", dir, meta.filename),
+                None => meta.filename.clone(),
+            };
+            (jar_path, meta.download.hash_format.clone(), meta.download.hash.clone(), origin(&meta))
+        } else {
+            let hash_format = entry.hash_format.clone().unwrap_or_else(|| index.hash_format.clone());
+            (entry.file.clone(), hash_format, entry.hash.clone(), None)
+        };
+
+        let Some(file_name) = jar_path.strip_prefix(&format!("{}/", MODS_DIR)) else { continue };
+        if !file_name.ends_with(".jar") || !options.accepts(file_name) {
+            continue;
+        }
+        listed.insert(file_name.to_string());
+        if let Some(origin) = origin {
+            origins.insert(file_name.to_string(), origin);
+        }
+
+        if root.join(&jar_path).is_file() {
+            let source = if entry.metafile { entry.file.as_str() } else { pack.index.file.as_str() };
+            check_hash(&root.join(&jar_path), &jar_path, &hash_format, &hash, source, &mut warnings)?;
+        } else if entry.metafile {
+            unavailable.push(file_name.to_string());
+        } else {
+            warnings.push(format!("{} is listed in {} but missing", jar_path, pack.index.file));
+        }
+    }
+
+    for file_name in jar_names(&mods_dir, options)? {
+        if !listed.contains(&file_name) {
+            warnings.push(format!("{}/{} is not listed in {}, run `packwiz refresh`", MODS_DIR, file_name, pack.index.file));
+        }
+    }
+
+    Ok(Pack {
+        mods: Vec::new(),
+        files: HashMap::new(),
+        mods_dir: Some(mods_dir),
+        environment: environment(&pack.versions),
+        unavailable,
+        origins,
+        warnings,
+        skipped: Vec::new(),
+    })
+}
+
+fn read_toml<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| Error::io("read", path, e))?;
+    toml::from_str(&contents).map_err(|e| Error::metadata("the pack", path.display().to_string(), e))
+}
+
+/// Compares the hash of the file at `path`, declared as `name` in `source`,
+/// with `expected`, reporting mismatches and unsupported formats to `warnings`.
+fn check_hash(
+    path: &Path,
+    name: &str,
+    hash_format: &str,
+    expected: &str,
+    source: &str,
+    warnings: &mut Vec<String>,
+) -> Result<()> {
+    let format = match hash_format.parse::<HashFormat>() {
+        Ok(format) => format,
+        Err(e) => {
+            warnings.push(format!("Can't check {} against {}: {}", name, source, e));
+            return Ok(());
+        }
+    };
+    if !hash_file(path, format)?.eq_ignore_ascii_case(expected) {
+        warnings.push(format!("{} doesn't match its {} hash in {}", name, hash_format, source));
+    }
+    Ok(())
+}
+
+/// The jars in `dir` and its subdirectories, relative to it.
+fn jar_names(dir: &Path, options: &ScanOptions) -> Result<Vec<String>> {
+    let mut names = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(current) = dirs.pop() {
+        let Ok(entries) = std::fs::read_dir(&current) else { continue };
+        for entry in entries {
+            let path = entry.map_err(|e| Error::io("read directory", &current, e))?.path();
+            if path.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            let relative = path.strip_prefix(dir).unwrap_or(&path)
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            if relative.ends_with(".jar") && options.accepts(&relative) {
+                names.push(relative);
+            }
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+fn origin(meta: &MetaFile) -> Option<Origin> {
+    let update = meta.update.as_ref()?;
+    if let Some(curseforge) = &update.curseforge {
+        return Some(Origin::CurseForge { project_id: curseforge.project_id, file_id: curseforge.file_id });
+    }
+    update.modrinth.as_ref().map(|modrinth| Origin::Modrinth {
+        project_id: modrinth.mod_id.clone(),
+        version_id: modrinth.version.clone(),
+    })
+}
+
+fn environment(versions: &HashMap<String, String>) -> Environment {
+    let loader = [
+        ("fabric", Platform::Fabric),
+        ("quilt", Platform::Quilt),
+        ("forge", Platform::Forge),
+        ("neoforge", Platform::NeoForge),
+    ]
+        .into_iter()
+        .find_map(|(key, platform)| versions.get(key).map(|version| (platform, version.clone())));
+
+    Environment { minecraft: versions.get("minecraft").cloned(), loader }
+}
+
+/// Writes a `.pw.toml` for every jar of `mods` found in `files` to the `mods`
+/// directory of `output`, and regenerates its `index.toml`.
+///
+/// Jars from CurseForge or Modrinth are downloaded from there. Jars of unknown
+/// origin get an empty download URL for the user to fill in, and are reported
+/// as incomplete; they are never copied into the pack.
+///
+/// Entries of the existing index are kept, except `.pw.toml` files describing
+/// an exported jar or another file of its project, which are replaced. The
+/// index hash in `pack.toml` is updated if there is one.
+pub fn export_packwiz(
+    mods: &[ModMetadata],
+    files: &HashMap<String, ModFile>,
+    origins: &HashMap<String, Origin>,
+    output: &Path,
+) -> Result<Exported> {
+    let mut exported_jars = HashSet::new();
+    let mut jars = Vec::new();
+    for mod_ in mods {
+        if !exported_jars.insert(mod_.file_name.as_str()) {
+            continue;
+        }
+        if let Some(file) = files.get(&mod_.file_name) {
+            jars.push((mod_, file, origins.get(&mod_.file_name)));
+        }
+    }
+
+    let mods_dir = output.join(MODS_DIR);
+    std::fs::create_dir_all(&mods_dir)
+        .map_err(|e| Error::io("create", &mods_dir, e))?;
+
+    let (pack, index_file) = read_pack_toml(output)?;
+    let mut entries = Vec::new();
+    let mut used_names = HashSet::new();
+    for entry in old_entries(output, &index_file)? {
+        let replaced = if entry.metafile && entry.file.starts_with(&format!("{}/", MODS_DIR)) {
+            let meta: MetaFile = read_toml(&output.join(&entry.file))?;
+            let old_origin = origin(&meta);
+            jars.iter().any(|(mod_, _, origin)| {
+                file_name(mod_) == meta.filename
+                    || origin.zip(old_origin.as_ref()).is_some_and(|(a, b)| same_project(a, b))
+            })
+        } else {
+            false
+        };
+        if replaced {
+            let path = output.join(&entry.file);
+            std::fs::remove_file(&path)
+                .map_err(|e| Error::io("remove", &path, e))?;
+            continue;
+        }
+        used_names.insert(entry.file.clone());
+        entries.push(entry);
+    }
+
+    let mut written = Vec::new();
+    let mut incomplete = Vec::new();
+    for (mod_, file, origin) in jars {
+        let slug = slug(&mod_.mod_id);
+        let mut name = format!("{}/{}.pw.toml", MODS_DIR, slug);
+        let mut n = 1;
+        while !used_names.insert(name.clone()) {
+            n += 1;
+            name = format!("{}/{}-{}.pw.toml", MODS_DIR, slug, n);
+        }
+        let meta = meta_file(mod_, file.hashes()?, origin);
+        let contents = toml::to_string(&meta)
+            .map_err(|e| Error::metadata(&mod_.file_name, &name, e))?;
+
+        let path = output.join(&name);
+        std::fs::write(&path, &contents)
+            .map_err(|e| Error::io("write", &path, e))?;
+        entries.push(IndexEntry {
+            file: name,
+            hash: hash_bytes(contents.as_bytes(), HashFormat::Sha256),
+            hash_format: None,
+            metafile: true,
+        });
+        if origin.is_none() {
+            incomplete.push(path.clone());
+        }
+        written.push(path);
+    }
+
+    written.extend(write_index(output, pack, &index_file, entries)?);
+    Ok(Exported { written, incomplete })
+}
+
+/// The `pack.toml` at `output`, if there is one, and the path of its index.
+fn read_pack_toml(output: &Path) -> Result<(Option<toml::Table>, String)> {
+    let pack_path = output.join(PACK_FILE);
+    let pack: Option<toml::Table> = pack_path.is_file().then(|| read_toml(&pack_path)).transpose()?;
+    let index_file = pack.as_ref()
+        .and_then(|pack| pack.get("index")?.get("file")?.as_str())
+        .unwrap_or("index.toml")
+        .to_string();
+    Ok((pack, index_file))
+}
+
+/// The entries of the index at `index_file` whose files still exist, each with
+/// its hash format.
+fn old_entries(output: &Path, index_file: &str) -> Result<Vec<IndexEntry>> {
+    let index_path = output.join(index_file);
+    if !index_path.is_file() {
+        return Ok(Vec::new());
+    }
+    let old: IndexToml = read_toml(&index_path)?;
+    Ok(old.files.into_iter()
+        .filter(|entry| output.join(&entry.file).is_file())
+        .map(|entry| IndexEntry { hash_format: entry.hash_format.or(Some(old.hash_format.clone())), ..entry })
+        .collect())
+}
+
+fn same_project(a: &Origin, b: &Origin) -> bool {
+    match (a, b) {
+        (Origin::CurseForge { project_id: a, .. }, Origin::CurseForge { project_id: b, .. }) => a == b,
+        (Origin::Modrinth { project_id: a, .. }, Origin::Modrinth { project_id: b, .. }) => a == b,
+        _ => false,
+    }
+}
+
+/// Writes `entries` as the index of the pack at `output`, and points its
+/// `pack.toml` at it if there is one.
+fn write_index(
+    output: &Path,
+    pack: Option<toml::Table>,
+    index_file: &str,
+    entries: Vec<IndexEntry>,
+) -> Result<Vec<PathBuf>> {
+    let index_path = output.join(index_file);
+    let index = IndexToml { hash_format: "sha256".to_string(), files: entries };
+    let contents = toml::to_string(&index)
+        .map_err(|e| Error::metadata("the pack", index_file, e))?;
+    std::fs::write(&index_path, &contents)
+        .map_err(|e| Error::io("write", &index_path, e))?;
+    let mut written = vec![index_path];
+
+    if let Some(mut pack) = pack {
+        let pack_path = output.join(PACK_FILE);
+        let mut index_ref = toml::Table::new();
+        index_ref.insert("file".to_string(), index_file.into());
+        index_ref.insert("hash-format".to_string(), "sha256".into());
+        index_ref.insert("hash".to_string(), hash_bytes(contents.as_bytes(), HashFormat::Sha256).into());
+        pack.insert("index".to_string(), index_ref.into());
+        let contents = toml::to_string(&pack)
+            .map_err(|e| Error::metadata("the pack", PACK_FILE, e))?;
+        std::fs::write(&pack_path, contents)
+            .map_err(|e| Error::io("write", &pack_path, e))?;
+        written.push(pack_path);
+    }
+    Ok(written)
+}
+
+/// The `.pw.toml` of `mod_`, with an empty download URL if its `origin` is unknown.
+fn meta_file(mod_: &ModMetadata, hashes: &FileHashes, origin: Option<&Origin>) -> MetaFile {
+    let side = match mod_.side {
+        Side::Both => "both",
+        Side::Client => "client",
+        Side::Server => "server",
+    };
+    let filename = file_name(mod_).to_string();
+    let (url, mode, update) = match origin {
+        // packwiz looks the download URL up by id.
+        Some(Origin::CurseForge { project_id, file_id }) => (
+            None,
+            Some("metadata:curseforge".to_string()),
+            Some(Update {
+                curseforge: Some(CurseForgeUpdate { project_id: *project_id, file_id: *file_id }),
+                modrinth: None,
+            }),
+        ),
+        Some(Origin::Modrinth { project_id, version_id }) => (
+            Some(format!(
+                "https://cdn.modrinth.com/data/{}/versions/{}/{}",
+                project_id, version_id, percent_encode(&filename)
+            )),
+            None,
+            Some(Update {
+                curseforge: None,
+                modrinth: Some(ModrinthUpdate { mod_id: project_id.clone(), version: version_id.clone() }),
+            }),
+        ),
+        None => (Some(String::new()), None, None),
+    };
+
+    MetaFile {
+        name: mod_.name.clone().unwrap_or_else(|| mod_.mod_id.clone()),
+        filename,
+        side: Some(side.to_string()),
+        download: Download {
+            url,
+            mode,
+            hash_format: "sha512".to_string(),
+            hash: hashes.sha512.clone(),
+        },
+        update,
+    }
+}
+
+/// The name of the jar of `mod_`, without the directory it is in.
+fn file_name(mod_: &ModMetadata) -> &str {
+    mod_.file_name.rsplit('/').next().unwrap_or(&mod_.file_name)
+}
+
+/// Escapes `s` for use as a URL path segment.
+fn percent_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn slug(mod_id: &str) -> String {
+    mod_id.to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_meta_file_round_trip() {
+        let contents = r#"
+name = "Sodium"
+filename = "sodium-fabric-0.5.8+mc1.20.1.jar"
+side = "client"
+
+[download]
+url = "https://cdn.modrinth.com/data/AANobbMI/versions/b4hTi3mo/sodium-fabric-0.5.8%2Bmc1.20.1.jar"
+hash-format = "sha1"
+hash = "4c5ca7d5ef6e5d8bd4ab3bb5e3e1e2d1b0c0e6f4"
+
+[update.modrinth]
+mod-id = "AANobbMI"
+version = "b4hTi3mo"
+"#;
+        let meta: MetaFile = toml::from_str(contents).unwrap();
+        assert_eq!(origin(&meta), Some(Origin::Modrinth {
+            project_id: "AANobbMI".to_string(),
+            version_id: "b4hTi3mo".to_string(),
+        }));
+        assert_eq!(toml::from_str::<MetaFile>(&toml::to_string(&meta).unwrap()).unwrap(), meta);
+    }
+
+    #[test]
+    fn test_export_packwiz() {
+        let dir = std::env::temp_dir().join(format!("mmod-packwiz-test-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("config")).unwrap();
+        std::fs::write(dir.join("config/app.toml"), b"").unwrap();
+        std::fs::write(dir.join("index.toml"), format!(
+            "hash-format = \"sha256\"\n\n[[files]]\nfile = \"config/app.toml\"\nhash = \"{}\"\n",
+            hash_bytes(b"", HashFormat::Sha256),
+        )).unwrap();
+        std::fs::write(dir.join(PACK_FILE), concat!(
+            "name = \"Example\"\n",
+            "[index]\nfile = \"index.toml\"\nhash-format = \"sha256\"\nhash = \"\"\n",
+            "[versions]\nminecraft = \"1.20.1\"\n",
+        )).unwrap();
+
+        let mods = [
+            ModMetadata::new("sodium", "0.5.8").with_file_name("sodium+mc1.20.1.jar"),
+            ModMetadata::new("jei", "15.2.0").with_file_name("jei.jar"),
+            ModMetadata::new("local", "1.0.0").with_file_name("local.jar").with_side(Side::Server),
+        ];
+        let files = HashMap::from([
+            ("sodium+mc1.20.1.jar".to_string(), ModFile::of(None, b"sodium")),
+            ("jei.jar".to_string(), ModFile::of(None, b"jei")),
+            ("local.jar".to_string(), ModFile::of(None, b"local")),
+        ]);
+        let sodium = Origin::Modrinth { project_id: "AANobbMI".to_string(), version_id: "b4hTi3mo".to_string() };
+        let jei = Origin::CurseForge { project_id: 238222, file_id: 4712345 };
+        let origins = HashMap::from([
+            ("sodium+mc1.20.1.jar".to_string(), sodium.clone()),
+            ("jei.jar".to_string(), jei.clone()),
+        ]);
+        let exported = export_packwiz(&mods, &files, &origins, &dir).unwrap();
+        assert_eq!(exported.incomplete, [dir.join("mods/local.pw.toml")]);
+
+        let meta: MetaFile = read_toml(&dir.join("mods/sodium.pw.toml")).unwrap();
+        assert_eq!(
+            meta.download.url.as_deref(),
+            Some("https://cdn.modrinth.com/data/AANobbMI/versions/b4hTi3mo/sodium%2Bmc1.20.1.jar"),
+        );
+        let meta: MetaFile = read_toml(&dir.join("mods/jei.pw.toml")).unwrap();
+        assert_eq!(meta.download.mode.as_deref(), Some("metadata:curseforge"));
+        let meta: MetaFile = read_toml(&dir.join("mods/local.pw.toml")).unwrap();
+        assert_eq!(meta.download.url.as_deref(), Some(""));
+        assert_eq!(meta.download.hash, FileHashes::of(b"local").sha512);
+        assert_eq!(meta.side.as_deref(), Some("server"));
+        assert!(!dir.join("mods/local.jar").exists());
+
+        // Reading the pack back checks the hashes of pack.toml and index.toml.
+        let pack = read_packwiz(&dir.join(PACK_FILE), &ScanOptions::new(false, &[], &[]).unwrap()).unwrap();
+        assert!(pack.warnings.is_empty(), "{:?}", pack.warnings);
+        assert_eq!(pack.origins, HashMap::from([
+            ("sodium+mc1.20.1.jar".to_string(), sodium),
+            ("jei.jar".to_string(), jei),
+        ]));
+        let index: IndexToml = read_toml(&dir.join("index.toml")).unwrap();
+        assert!(index.files.iter().any(|entry| entry.file == "config/app.toml"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_export_packwiz_keeps_metafiles() {
+        let dir = std::env::temp_dir().join(format!("mmod-packwiz-metafiles-test-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("mods")).unwrap();
+        let sodium = concat!(
+            "name = \"Sodium\"\nfilename = \"sodium.jar\"\nside = \"client\"\n",
+            "[download]\nurl = \"https://cdn.modrinth.com/data/AANobbMI/versions/b4hTi3mo/sodium.jar\"\n",
+            "hash-format = \"sha1\"\nhash = \"00\"\n",
+            "[update.modrinth]\nmod-id = \"AANobbMI\"\nversion = \"b4hTi3mo\"\n",
+        );
+        let jei = concat!(
+            "name = \"JEI\"\nfilename = \"jei-15.2.0.jar\"\nside = \"both\"\n",
+            "[download]\nmode = \"metadata:curseforge\"\nhash-format = \"sha1\"\nhash = \"00\"\n",
+            "[update.curseforge]\nproject-id = 238222\nfile-id = 4712345\n",
+        );
+        std::fs::write(dir.join("mods/sodium.pw.toml"), sodium).unwrap();
+        std::fs::write(dir.join("mods/just-enough-items.pw.toml"), jei).unwrap();
+        std::fs::write(dir.join("index.toml"), format!(
+            concat!(
+                "hash-format = \"sha256\"\n",
+                "[[files]]\nfile = \"mods/sodium.pw.toml\"\nhash = \"{}\"\nmetafile = true\n",
+                "[[files]]\nfile = \"mods/just-enough-items.pw.toml\"\nhash = \"{}\"\nmetafile = true\n",
+            ),
+            hash_bytes(sodium.as_bytes(), HashFormat::Sha256),
+            hash_bytes(jei.as_bytes(), HashFormat::Sha256),
+        )).unwrap();
+        std::fs::write(dir.join(PACK_FILE), concat!(
+            "name = \"Example\"\n",
+            "[index]\nfile = \"index.toml\"\nhash-format = \"sha256\"\nhash = \"\"\n",
+            "[versions]\nminecraft = \"1.20.1\"\n",
+        )).unwrap();
+        let options = ScanOptions::new(false, &[], &[]).unwrap();
+
+        // None of the jars are available, so exporting the pack leaves it as it was.
+        export_packwiz(&[], &HashMap::new(), &HashMap::new(), &dir).unwrap();
+        let pack = read_packwiz(&dir.join(PACK_FILE), &options).unwrap();
+        assert!(pack.warnings.is_empty(), "{:?}", pack.warnings);
+        let mut unavailable = pack.unavailable.clone();
+        unavailable.sort();
+        assert_eq!(unavailable, ["jei-15.2.0.jar", "sodium.jar"]);
+        assert_eq!(pack.origins.len(), 2);
+
+        // A new file of the JEI project replaces its metafile, the other one is kept.
+        let mods = [ModMetadata::new("jei", "15.3.0").with_file_name("jei-15.3.0.jar")];
+        let files = HashMap::from([("jei-15.3.0.jar".to_string(), ModFile::of(None, b"jei"))]);
+        let origins = HashMap::from([
+            ("jei-15.3.0.jar".to_string(), Origin::CurseForge { project_id: 238222, file_id: 4812345 }),
+        ]);
+        export_packwiz(&mods, &files, &origins, &dir).unwrap();
+        assert!(!dir.join("mods/just-enough-items.pw.toml").exists());
+        let pack = read_packwiz(&dir.join(PACK_FILE), &options).unwrap();
+        assert!(pack.warnings.is_empty(), "{:?}", pack.warnings);
+        let mut unavailable = pack.unavailable.clone();
+        unavailable.sort();
+        assert_eq!(unavailable, ["jei-15.3.0.jar", "sodium.jar"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
\ No newline at end of file
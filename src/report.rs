@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use crate::diagram::{self, escape_xml, DiagramFormat};
+use mmod::pack::Origin;
+use mmod::{DependencyError, DependencyGraph, ModMetadata, Severity};
+
+const STYLE: &str = r#"
+body { font-family: sans-serif; margin: 2em; color: #222; }
+h1 { margin-bottom: 0.2em; }
+table { border-collapse: collapse; width: 100%; }
+th, td { border: 1px solid #ccc; padding: 4px 8px; text-align: left; vertical-align: top; }
+th { background: #f0f0f0; cursor: pointer; user-select: none; }
+th[data-order="asc"]::after { content: " ▲"; }
+th[data-order="desc"]::after { content: " ▼"; }
+tr:nth-child(even) td { background: #fafafa; }
+.diagnostic { white-space: pre-wrap; font-family: monospace; padding: 6px 8px; margin: 4px 0; border-left: 4px solid; }
+.error { border-color: #c00; background: #fff0f0; }
+.warning { border-color: #e69500; background: #fff8e6; }
+.graph { overflow: auto; border: 1px solid #ccc; padding: 8px; }
+"#;
+
+// Sorts the mod table by the clicked column, toggling the direction on each click.
+const SCRIPT: &str = r#"
+document.querySelectorAll("table.sortable th").forEach(function (th, column) {
+  th.addEventListener("click", function () {
+    var table = th.closest("table");
+    var body = table.tBodies[0];
+    var order = th.dataset.order === "asc" ? "desc" : "asc";
+    table.querySelectorAll("th").forEach(function (other) { delete other.dataset.order; });
+    th.dataset.order = order;
+    var rows = Array.prototype.slice.call(body.rows);
+    rows.sort(function (a, b) {
+      var x = a.cells[column].textContent, y = b.cells[column].textContent;
+      var result = x.localeCompare(y, undefined, { numeric: true, sensitivity: "base" });
+      return order === "asc" ? result : -result;
+    });
+    rows.forEach(function (row) { body.appendChild(row); });
+  });
+});
+"#;
+
+/// Renders a single self-contained HTML page with the mod list, the diagnostics
+/// grouped by severity and the dependency graph. No external assets are referenced.
+///
+/// `origins` tells, by file name, where the jars of a modpack were downloaded from.
+pub fn render_html(
+    mods: &[ModMetadata],
+    errors: &[DependencyError],
+    graph: &DependencyGraph,
+    origins: &HashMap<String, Origin>,
+) -> String {
+    let mut out = String::new();
+
+    writeln!(out, "<!DOCTYPE html>").unwrap();
+    writeln!(out, "<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">").unwrap();
+    writeln!(out, "<title>Mod report</title>\n<style>{}</style>\n</head>\n<body>", STYLE).unwrap();
+    writeln!(out, "<h1>Mod report</h1>").unwrap();
+    writeln!(
+        out,
+        "<p>{} mods, {} errors, {} warnings</p>",
+        mods.len(),
+        errors.iter().filter(|e| e.severity() == Severity::Error).count(),
+        errors.iter().filter(|e| e.severity() == Severity::Warning).count(),
+    ).unwrap();
+
+    writeln!(out, "<h2>Diagnostics</h2>").unwrap();
+    if errors.is_empty() {
+        writeln!(out, "<p>All dependencies are satisfied.</p>").unwrap();
+    }
+    for (severity, title, class) in [(Severity::Error, "Errors", "error"), (Severity::Warning, "Warnings", "warning")] {
+        let group: Vec<_> = errors.iter().filter(|e| e.severity() == severity).collect();
+        if group.is_empty() {
+            continue;
+        }
+        writeln!(out, "<h3>{} ({})</h3>", title, group.len()).unwrap();
+        for error in group {
+            writeln!(out, "<div class=\"diagnostic {}\">{}</div>", class, escape_xml(&error.to_string())).unwrap();
+        }
+    }
+
+    writeln!(out, "<h2>Mods</h2>").unwrap();
+    writeln!(out, "<table class=\"sortable\">").unwrap();
+    writeln!(out, "<thead><tr><th>Name</th><th>Id</th><th>Version</th><th>Authors</th><th>File</th><th>Platform</th><th>Source</th></tr></thead>").unwrap();
+    writeln!(out, "<tbody>").unwrap();
+    let mut sorted: Vec<_> = mods.iter().collect();
+    sorted.sort_by(|a, b| a.mod_id.cmp(&b.mod_id));
+    for mod_ in sorted {
+        writeln!(
+            out,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape_xml(mod_.name.as_deref().unwrap_or(&mod_.mod_id)),
+            escape_xml(&mod_.mod_id),
+            escape_xml(&mod_.version),
+            escape_xml(&mod_.authors.join(", ")),
+            escape_xml(&mod_.file_name),
+            escape_xml(&diagram::platform_name(&mod_.platform)),
+            origins.get(&mod_.file_name).map(|origin| escape_xml(&origin.to_string())).unwrap_or_default(),
+        ).unwrap();
+    }
+    writeln!(out, "</tbody>\n</table>").unwrap();
+
+    writeln!(out, "<h2>Dependency graph</h2>").unwrap();
+    writeln!(out, "<div class=\"graph\">\n{}</div>", diagram::render(graph, DiagramFormat::Svg)).unwrap();
+
+    writeln!(out, "<script>{}</script>\n</body>\n</html>", SCRIPT).unwrap();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mmod::{analyze_dependencies, Platform};
+
+    #[test]
+    fn test_render_html_escapes_metadata() {
+        let mut app = ModMetadata::new("app", "1.0.0")
+            .with_file_name("a&b.jar")
+            .with_platform(Platform::Unknown("<loader>".to_string()))
+            .with_dependencies(&[("<missing>", "*")]);
+        app.name = Some("Tools & <Things>".to_string());
+        app.authors = vec!["\"Quoted\"".to_string()];
+        let mods = vec![app];
+        let errors = analyze_dependencies(&mods, None).unwrap_err().0;
+        let origins = HashMap::from([(
+            "a&b.jar".to_string(),
+            Origin::Modrinth { project_id: "<p>".to_string(), version_id: "v&1".to_string() },
+        )]);
+        let refs: Vec<_> = mods.iter().collect();
+        let html = render_html(&mods, &errors, &DependencyGraph::new(&refs), &origins);
+
+        assert!(html.contains("<td>Tools &amp; &lt;Things&gt;</td>"), "{}", html);
+        assert!(html.contains("<td>a&amp;b.jar</td><td>&lt;loader&gt;</td><td>Modrinth project &lt;p&gt;, version v&amp;1</td>"), "{}", html);
+        assert!(html.contains("&lt;missing&gt;"), "{}", html);
+        assert!(!html.contains("<missing>") && !html.contains("<loader>") && !html.contains("<Things>"), "{}", html);
+    }
+}
@@ -0,0 +1,267 @@
+mod pubgrub;
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+use anyhow::Result;
+use mmod::{check_environment, compare_versions, is_platform_dependency, scan_dirs, select_loader_entries, Environment, ModMetadata, Platform, ScanOptions};
+use self::pubgrub::{Dependency, Package, Version, VersionSet, ROOT};
+
+/// Why no version of each mod satisfies every dependency, one derivation step per line.
+#[derive(Debug)]
+pub struct Unsatisfiable(pub Vec<String>);
+
+impl fmt::Display for Unsatisfiable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0.join("\n"))
+    }
+}
+
+/// The jars of `repo` and its subdirectories that can run on `loader` and
+/// in `environment`, named by their path. Returns them with the number of
+/// jars left out.
+pub fn load_candidates(
+    repo: &Path,
+    options: &ScanOptions,
+    loader: Option<&Platform>,
+    environment: &Environment,
+) -> Result<(Vec<ModMetadata>, usize)> {
+    let scanned = scan_dirs(&[repo.to_path_buf()], options)?;
+    for (file_name, error) in &scanned.skipped {
+        eprintln!("[!] Skipping {}: {}", repo.join(file_name).display(), error);
+    }
+    let (mut mods, skipped) = select_candidates(scanned.enabled, loader, environment);
+    for mod_ in mods.iter_mut() {
+        mod_.file_name = repo.join(&mod_.file_name).display().to_string();
+    }
+    Ok((mods, skipped))
+}
+
+/// Keeps the entries of `mods` that can run on `loader` and in `environment`,
+/// and counts the jars left out entirely.
+fn select_candidates(
+    mut mods: Vec<ModMetadata>,
+    loader: Option<&Platform>,
+    environment: &Environment,
+) -> (Vec<ModMetadata>, usize) {
+    let jars = |mods: &[ModMetadata]| mods.iter().map(|mod_| mod_.file_name.as_str()).collect::<HashSet<_>>().len();
+    let found = jars(&mods);
+    if let Some(loader) = loader {
+        mods = select_loader_entries(mods, loader);
+        mods.retain(|mod_| &mod_.platform == loader);
+    }
+    mods.retain(|mod_| check_environment(std::slice::from_ref(mod_), environment).is_empty());
+
+    let skipped = found - jars(&mods);
+    (mods, skipped)
+}
+
+/// Picks a version of each of `required`, and of their mandatory dependencies,
+/// among `candidates` such that every dependency's version range is satisfied.
+///
+/// Newer versions are preferred, and earlier candidates among jars of the
+/// same version. Dependencies on the game, loaders or Java are left out.
+pub fn resolve<'a>(required: &'a [String], candidates: &'a [ModMetadata]) -> Result<Vec<&'a ModMetadata>, Unsatisfiable> {
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    let mut versions: Vec<Vec<&ModMetadata>> = vec![Vec::new()];
+    let mut names = vec!["the mod set"];
+    let mut package_of = |mod_id: &'a str, versions: &mut Vec<Vec<&'a ModMetadata>>| {
+        *index.entry(mod_id).or_insert_with(|| {
+            versions.push(Vec::new());
+            names.push(mod_id);
+            versions.len() - 1
+        })
+    };
+
+    for candidate in candidates {
+        let package = package_of(&candidate.mod_id, &mut versions);
+        if !versions[package].iter().any(|other| other.version == candidate.version) {
+            versions[package].push(candidate);
+        }
+    }
+    for candidate in candidates {
+        for mod_id in candidate.provides.iter()
+            .chain(candidate.dependencies.iter().map(|d| &d.mod_id).filter(|id| !is_platform_dependency(id)))
+        {
+            package_of(mod_id, &mut versions);
+        }
+    }
+    let required: Vec<usize> = required.iter().map(|mod_id| package_of(mod_id, &mut versions)).collect();
+    for candidates in versions.iter_mut() {
+        candidates.sort_by(|a, b| compare_versions(&b.version, &a.version));
+    }
+
+    // A jar is also a candidate for the ids it provides, after the jars of
+    // those ids. Selecting it that way selects the jar itself.
+    let mut providers: Vec<Vec<(usize, usize)>> = vec![Vec::new(); versions.len()];
+    for (package, candidates) in versions.iter().enumerate() {
+        for (version, candidate) in candidates.iter().enumerate() {
+            for mod_id in candidate.provides.iter().filter(|id| **id != candidate.mod_id) {
+                providers[index[mod_id.as_str()]].push((package, version));
+            }
+        }
+    }
+    let version_of = |package: usize, version: usize| -> &str {
+        match version.checked_sub(versions[package].len()) {
+            None => &versions[package][version].version,
+            Some(provided) => {
+                let (provider, provider_version) = providers[package][provided];
+                &versions[provider][provider_version].version
+            }
+        }
+    };
+    let allowed = |package: usize, allows: &dyn Fn(&str) -> bool| -> VersionSet {
+        (0..versions[package].len() + providers[package].len())
+            .filter(|&version| allows(version_of(package, version)))
+            .collect()
+    };
+
+    let root = Version {
+        label: String::new(),
+        dependencies: required.iter()
+            .map(|&package| Dependency {
+                package,
+                allowed: allowed(package, &|_| true),
+                mandatory: true,
+                range: String::new(),
+            })
+            .collect(),
+    };
+    let mut packages = vec![Package { name: names[ROOT].to_string(), versions: vec![root] }];
+    for (package, candidates) in versions.iter().enumerate().skip(1) {
+        let own = candidates.iter().map(|candidate| Version {
+            label: candidate.version.clone(),
+            dependencies: candidate.dependencies.iter()
+                .filter(|dependency| !is_platform_dependency(&dependency.mod_id))
+                .map(|dependency| Dependency {
+                    package: index[dependency.mod_id.as_str()],
+                    allowed: allowed(index[dependency.mod_id.as_str()], &|version| dependency.version_range.allows(version)),
+                    mandatory: dependency.mandatory,
+                    range: dependency.version_range.to_string(),
+                })
+                .collect(),
+        });
+        let provided = providers[package].iter().map(|&(provider, version)| {
+            let candidate = versions[provider][version];
+            Version {
+                label: format!("{} (provided by {})", candidate.version, candidate.mod_id),
+                dependencies: vec![Dependency {
+                    package: provider,
+                    allowed: VersionSet::from([version]),
+                    mandatory: true,
+                    range: candidate.version.clone(),
+                }],
+            }
+        });
+        packages.push(Package { name: names[package].to_string(), versions: own.chain(provided).collect() });
+    }
+
+    let selection = pubgrub::solve(&packages).map_err(Unsatisfiable)?;
+    let mut selected: Vec<&ModMetadata> = selection.into_iter()
+        .filter_map(|(package, version)| versions[package].get(version).copied())
+        .collect();
+    selected.sort_by(|a, b| a.mod_id.cmp(&b.mod_id));
+    Ok(selected)
+}
+
+/// Prints the selected jars, pointing out those that differ from `installed`.
+pub fn print_selection(selected: &[&ModMetadata], installed: &[ModMetadata]) {
+    println!("Selected versions:");
+    for mod_ in selected {
+        match installed.iter().find(|other| other.mod_id == mod_.mod_id) {
+            Some(other) if other.file_name == mod_.file_name => {
+                println!("  {} {} ({})", mod_.mod_id, mod_.version, mod_.file_name)
+            }
+            Some(other) => println!(
+                "  {} {} ({}), installed {}",
+                mod_.mod_id, mod_.version, mod_.file_name, other.version
+            ),
+            None => println!("  {} {} ({}), new", mod_.mod_id, mod_.version, mod_.file_name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_names(selected: &[&ModMetadata]) -> Vec<String> {
+        selected.iter().map(|mod_| mod_.file_name.clone()).collect()
+    }
+
+    #[test]
+    fn test_counts_the_jars_left_out() {
+        let mods = vec![
+            ModMetadata::new("architectury", "9.1.0").with_file_name("architectury.jar"),
+            ModMetadata::new("architectury", "9.1.0").with_file_name("architectury.jar").with_platform(Platform::Forge),
+            ModMetadata::new("create", "0.5.1").with_platform(Platform::Forge),
+            ModMetadata::new("sodium", "0.5.8"),
+        ];
+        let (selected, skipped) = select_candidates(mods, Some(&Platform::Forge), &Environment::default());
+        let selected: Vec<_> = selected.iter().collect();
+        assert_eq!(file_names(&selected), vec!["architectury.jar", "create-0.5.1.jar"]);
+        assert_eq!(skipped, 1);
+    }
+
+    #[test]
+    fn test_backtracks_to_an_older_version() {
+        let candidates = vec![
+            ModMetadata::new("app", "2.0.0").with_dependencies(&[("lib", ">=2.0.0"), ("api", "*")]),
+            ModMetadata::new("app", "1.0.0").with_dependencies(&[("lib", "<2.0.0")]),
+            ModMetadata::new("lib", "2.0.0"),
+            ModMetadata::new("lib", "1.5.0"),
+            ModMetadata::new("api", "1.0.0").with_dependencies(&[("lib", "<2.0.0")]),
+            ModMetadata::new("other", "1.0.0").with_dependencies(&[("lib", "1.5.0"), ("fabricloader", ">=0.15")]),
+        ];
+        let required = vec!["app".to_string(), "other".to_string()];
+        let selected = resolve(&required, &candidates).unwrap();
+        assert_eq!(file_names(&selected), vec!["app-1.0.0.jar", "lib-1.5.0.jar", "other-1.0.0.jar"]);
+    }
+
+    #[test]
+    fn test_explains_conflicts() {
+        let candidates = vec![
+            ModMetadata::new("app", "1.0.0").with_dependencies(&[("lib", ">=2.0.0")]),
+            ModMetadata::new("other", "1.0.0").with_dependencies(&[("lib", "<2.0.0")]),
+            ModMetadata::new("lib", "2.0.0"),
+            ModMetadata::new("lib", "1.0.0"),
+        ];
+        let required = vec!["app".to_string(), "other".to_string()];
+        let explanation = resolve(&required, &candidates).unwrap_err().to_string();
+        assert!(explanation.contains("app 1.0.0 requires lib >=2.0.0"), "{}", explanation);
+        assert!(explanation.contains("other 1.0.0 requires lib <2.0.0"), "{}", explanation);
+        assert!(explanation.ends_with("no selection satisfies the mod set."), "{}", explanation);
+
+        let candidates = vec![ModMetadata::new("app", "1.0.0").with_dependencies(&[("missing", "*")])];
+        let explanation = resolve(&["app".to_string()], &candidates).unwrap_err().to_string();
+        assert!(explanation.contains("app 1.0.0 requires missing, which no candidate provides"), "{}", explanation);
+    }
+
+    #[test]
+    fn test_provided_ids_select_their_jar() {
+        let candidates = vec![
+            ModMetadata::new("a", "2.0.0").with_provides(&["x"]),
+            ModMetadata::new("a", "1.0.0").with_provides(&["x"]),
+            ModMetadata::new("app", "1.0.0").with_dependencies(&[("x", "<2.0.0")]),
+        ];
+        let required = vec!["app".to_string(), "a".to_string()];
+        let selected = resolve(&required, &candidates).unwrap();
+        assert_eq!(file_names(&selected), vec!["a-1.0.0.jar", "app-1.0.0.jar"]);
+
+        let candidates = vec![
+            ModMetadata::new("a", "1.0.0").with_provides(&["x"]),
+            ModMetadata::new("x", "1.0.0"),
+            ModMetadata::new("app", "1.0.0").with_dependencies(&[("x", "*")]),
+        ];
+        let required = vec!["app".to_string()];
+        let selected = resolve(&required, &candidates).unwrap();
+        assert_eq!(file_names(&selected), vec!["app-1.0.0.jar", "x-1.0.0.jar"]);
+
+        let candidates = vec![
+            ModMetadata::new("a", "2.0.0").with_provides(&["x"]),
+            ModMetadata::new("app", "1.0.0").with_dependencies(&[("x", "<2.0.0")]),
+        ];
+        let explanation = resolve(&required, &candidates).unwrap_err().to_string();
+        assert!(explanation.contains("app 1.0.0 requires x <2.0.0, which no candidate provides"), "{}", explanation);
+    }
+}
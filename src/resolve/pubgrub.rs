@@ -0,0 +1,592 @@
+// A PubGrub version solver, see https://github.com/dart-lang/pub/blob/master/doc/solver.md
+//
+// Every package has a finite list of candidate versions, so a set of versions
+// is just a set of indices into it.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Indices into the `versions` of a package.
+pub type VersionSet = BTreeSet<usize>;
+
+/// The package every solution selects. Its only version depends on the
+/// packages that are asked for.
+pub const ROOT: usize = 0;
+
+pub struct Package {
+    pub name: String,
+    /// The candidates, most preferred first.
+    pub versions: Vec<Version>,
+}
+
+pub struct Version {
+    pub label: String,
+    pub dependencies: Vec<Dependency>,
+}
+
+pub struct Dependency {
+    pub package: usize,
+    /// The versions of `package` the range allows.
+    pub allowed: VersionSet,
+    /// Whether `package` must be selected, or is only constrained when it is.
+    pub mandatory: bool,
+    /// The range as declared, for explanations.
+    pub range: String,
+}
+
+/// `package` is selected at one of `versions` when positive, or is not
+/// selected at any of them (which includes not being selected at all)
+/// otherwise.
+#[derive(Debug, Clone, PartialEq)]
+struct Term {
+    package: usize,
+    positive: bool,
+    versions: VersionSet,
+}
+
+impl Term {
+    fn any(package: usize) -> Term {
+        Term { package, positive: false, versions: VersionSet::new() }
+    }
+
+    fn selected(package: usize, version: usize) -> Term {
+        Term { package, positive: true, versions: VersionSet::from([version]) }
+    }
+
+    fn is_any(&self) -> bool {
+        !self.positive && self.versions.is_empty()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.positive && self.versions.is_empty()
+    }
+
+    fn negate(&self) -> Term {
+        Term { positive: !self.positive, ..self.clone() }
+    }
+
+    fn intersect(&self, other: &Term) -> Term {
+        let versions = match (self.positive, other.positive) {
+            (true, true) => self.versions.intersection(&other.versions).copied().collect(),
+            (true, false) => self.versions.difference(&other.versions).copied().collect(),
+            (false, true) => other.versions.difference(&self.versions).copied().collect(),
+            (false, false) => self.versions.union(&other.versions).copied().collect(),
+        };
+        Term { package: self.package, positive: self.positive || other.positive, versions }
+    }
+
+    fn union(&self, other: &Term) -> Term {
+        self.negate().intersect(&other.negate()).negate()
+    }
+
+    /// Whether every selection this term allows is allowed by `other`.
+    fn satisfies(&self, other: &Term) -> bool {
+        self.intersect(&other.negate()).is_empty()
+    }
+
+    fn contradicts(&self, other: &Term) -> bool {
+        self.intersect(other).is_empty()
+    }
+}
+
+enum Cause {
+    /// The root package must be selected.
+    Root,
+    /// The `dependency`-th dependency of a version.
+    Dependency { package: usize, version: usize, dependency: usize },
+    /// Derived from two incompatibilities during conflict resolution.
+    Derived(usize, usize),
+}
+
+/// Terms that can't all hold at once.
+struct Incompatibility {
+    terms: Vec<Term>,
+    cause: Cause,
+}
+
+impl Incompatibility {
+    fn new(terms: impl IntoIterator<Item = Term>, cause: Cause) -> Self {
+        let mut merged: Vec<Term> = Vec::new();
+        for term in terms {
+            match merged.iter_mut().find(|t| t.package == term.package) {
+                Some(t) => *t = t.intersect(&term),
+                None => merged.push(term),
+            }
+        }
+        // Always holding, so they don't constrain anything.
+        merged.retain(|term| !term.is_any());
+        Incompatibility { terms: merged, cause }
+    }
+
+    fn term(&self, package: usize) -> Term {
+        self.terms.iter().find(|term| term.package == package).cloned().unwrap_or(Term::any(package))
+    }
+
+    /// Whether it rules out any solution.
+    fn is_terminal(&self) -> bool {
+        match self.terms.as_slice() {
+            [] => true,
+            [term] => term.package == ROOT && term.positive,
+            _ => false,
+        }
+    }
+}
+
+enum Relation {
+    Satisfied,
+    /// Every term but this one is satisfied.
+    AlmostSatisfied(Term),
+    Contradicted,
+    Inconclusive,
+}
+
+struct Assignment {
+    term: Term,
+    level: usize,
+    /// The incompatibility it was derived from, `None` for decisions.
+    cause: Option<usize>,
+}
+
+struct Solver<'a> {
+    packages: &'a [Package],
+    incompatibilities: Vec<Incompatibility>,
+    /// The incompatibilities taking part in propagation, by package of their terms.
+    by_package: Vec<Vec<usize>>,
+    assignments: Vec<Assignment>,
+    /// The intersection of the assignments of each package.
+    accumulated: Vec<Term>,
+    decisions: Vec<Option<usize>>,
+    level: usize,
+    /// The versions whose dependencies have been added.
+    expanded: HashSet<(usize, usize)>,
+}
+
+/// Selects a version of `ROOT`, of each of its mandatory dependencies, and
+/// so on, that satisfies every dependency range, preferring earlier versions.
+/// Returns the selected `(package, version)` pairs besides the root one, or
+/// the lines explaining why there is no solution.
+pub fn solve(packages: &[Package]) -> Result<Vec<(usize, usize)>, Vec<String>> {
+    let mut solver = Solver {
+        packages,
+        incompatibilities: Vec::new(),
+        by_package: vec![Vec::new(); packages.len()],
+        assignments: Vec::new(),
+        accumulated: (0..packages.len()).map(Term::any).collect(),
+        decisions: vec![None; packages.len()],
+        level: 0,
+        expanded: HashSet::new(),
+    };
+    let root = solver.push(Incompatibility::new([Term::selected(ROOT, 0).negate()], Cause::Root));
+    solver.register(root);
+
+    let mut next = ROOT;
+    loop {
+        if let Err(conflict) = solver.propagate(next) {
+            return Err(solver.explain(conflict));
+        }
+        match solver.decide() {
+            Some(package) => next = package,
+            None => break,
+        }
+    }
+
+    Ok(solver.decisions.iter().enumerate()
+        .filter(|(package, _)| *package != ROOT)
+        .filter_map(|(package, version)| Some((package, (*version)?)))
+        .collect())
+}
+
+impl Solver<'_> {
+    fn push(&mut self, incompatibility: Incompatibility) -> usize {
+        self.incompatibilities.push(incompatibility);
+        self.incompatibilities.len() - 1
+    }
+
+    fn register(&mut self, id: usize) {
+        for term in &self.incompatibilities[id].terms {
+            self.by_package[term.package].push(id);
+        }
+    }
+
+    fn assign(&mut self, term: Term, cause: Option<usize>) {
+        let package = term.package;
+        self.accumulated[package] = self.accumulated[package].intersect(&term);
+        self.assignments.push(Assignment { term, level: self.level, cause });
+    }
+
+    fn backtrack(&mut self, level: usize) {
+        self.assignments.retain(|assignment| assignment.level <= level);
+        self.level = level;
+        self.recompute();
+    }
+
+    fn recompute(&mut self) {
+        self.accumulated = (0..self.packages.len()).map(Term::any).collect();
+        self.decisions = vec![None; self.packages.len()];
+        for assignment in &self.assignments {
+            let package = assignment.term.package;
+            self.accumulated[package] = self.accumulated[package].intersect(&assignment.term);
+            if assignment.cause.is_none() {
+                self.decisions[package] = assignment.term.versions.first().copied();
+            }
+        }
+    }
+
+    fn relation(&self, id: usize) -> Relation {
+        let mut unsatisfied = None;
+        for term in &self.incompatibilities[id].terms {
+            let accumulated = &self.accumulated[term.package];
+            if accumulated.satisfies(term) {
+                continue;
+            }
+            if accumulated.contradicts(term) {
+                return Relation::Contradicted;
+            }
+            if unsatisfied.is_some() {
+                return Relation::Inconclusive;
+            }
+            unsatisfied = Some(term.clone());
+        }
+        match unsatisfied {
+            Some(term) => Relation::AlmostSatisfied(term),
+            None => Relation::Satisfied,
+        }
+    }
+
+    /// Derives what the incompatibilities imply from the assignments of
+    /// `package`. Returns the terminal incompatibility on failure.
+    fn propagate(&mut self, package: usize) -> Result<(), usize> {
+        let mut changed = vec![package];
+        while let Some(package) = changed.pop() {
+            for id in self.by_package[package].clone().into_iter().rev() {
+                match self.relation(id) {
+                    Relation::Satisfied => {
+                        let cause = self.resolve_conflict(id)?;
+                        if let Relation::AlmostSatisfied(term) = self.relation(cause) {
+                            changed = vec![term.package];
+                            self.assign(term.negate(), Some(cause));
+                        }
+                        break;
+                    }
+                    Relation::AlmostSatisfied(term) => {
+                        changed.push(term.package);
+                        self.assign(term.negate(), Some(id));
+                    }
+                    Relation::Contradicted | Relation::Inconclusive => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Learns from the satisfied incompatibility `id` why it happened and
+    /// backtracks to where that can be avoided. Returns the learned
+    /// incompatibility, or the terminal one if there is no way around it.
+    fn resolve_conflict(&mut self, conflict: usize) -> Result<usize, usize> {
+        let mut id = conflict;
+        loop {
+            if self.incompatibilities[id].is_terminal() {
+                return Err(id);
+            }
+
+            let (satisfier, term, previous_level) = self.find_satisfier(id);
+            let assignment = &self.assignments[satisfier];
+            let Some(cause) = assignment.cause.filter(|_| previous_level == assignment.level) else {
+                if id != conflict {
+                    self.register(id);
+                }
+                self.backtrack(previous_level);
+                return Ok(id);
+            };
+
+            let package = term.package;
+            let merged = self.incompatibilities[id].term(package).union(&self.incompatibilities[cause].term(package));
+            let terms: Vec<Term> = self.incompatibilities[id].terms.iter()
+                .chain(self.incompatibilities[cause].terms.iter())
+                .filter(|term| term.package != package)
+                .cloned()
+                .chain([merged])
+                .collect();
+            id = self.push(Incompatibility::new(terms, Cause::Derived(id, cause)));
+        }
+    }
+
+    /// The earliest assignment after which `id` is satisfied, the term it
+    /// satisfies, and the decision level that satisfies the other terms.
+    fn find_satisfier(&self, id: usize) -> (usize, Term, usize) {
+        let terms = &self.incompatibilities[id].terms;
+        let mut accumulated: HashMap<usize, Term> = HashMap::new();
+        let mut satisfiers = vec![None; terms.len()];
+        for (index, assignment) in self.assignments.iter().enumerate() {
+            let package = assignment.term.package;
+            let Some(t) = terms.iter().position(|term| term.package == package) else { continue };
+            if satisfiers[t].is_some() {
+                continue;
+            }
+            let term = accumulated.entry(package).or_insert_with(|| Term::any(package));
+            *term = term.intersect(&assignment.term);
+            if term.satisfies(&terms[t]) {
+                satisfiers[t] = Some(index);
+            }
+        }
+
+        let satisfiers: Vec<usize> = satisfiers.into_iter()
+            .map(|satisfier| satisfier.expect("a satisfied incompatibility"))
+            .collect();
+        let (t, satisfier) = satisfiers.iter().copied().enumerate()
+            .max_by_key(|(_, satisfier)| *satisfier)
+            .expect("a terminal incompatibility");
+        let mut previous_level = satisfiers.iter().enumerate()
+            .filter(|(other, _)| *other != t)
+            .map(|(_, satisfier)| self.assignments[*satisfier].level)
+            .max()
+            .unwrap_or(1)
+            .max(1);
+
+        // When the satisfier only narrows down earlier assignments of its
+        // package, those are needed too.
+        let term = &terms[t];
+        let assignment = &self.assignments[satisfier];
+        if !assignment.term.satisfies(term) {
+            let mut accumulated = Term::any(term.package);
+            for earlier in self.assignments[..satisfier].iter().filter(|a| a.term.package == term.package) {
+                accumulated = accumulated.intersect(&earlier.term);
+                if accumulated.intersect(&assignment.term).satisfies(term) {
+                    previous_level = previous_level.max(earlier.level);
+                    break;
+                }
+            }
+        }
+        (satisfier, term.clone(), previous_level)
+    }
+
+    /// Selects the preferred allowed version of the package with the fewest
+    /// allowed versions, adding its dependencies. Returns `None` once every
+    /// required package is selected.
+    fn decide(&mut self) -> Option<usize> {
+        let package = (0..self.packages.len())
+            .filter(|&package| self.decisions[package].is_none() && self.accumulated[package].positive)
+            .min_by_key(|&package| self.accumulated[package].versions.len())?;
+        let version = *self.accumulated[package].versions.first()?;
+
+        let mut added = Vec::new();
+        if self.expanded.insert((package, version)) {
+            for (index, dependency) in self.packages[package].versions[version].dependencies.iter().enumerate() {
+                if dependency.package == package {
+                    continue;
+                }
+                let term = if dependency.mandatory {
+                    Term { package: dependency.package, positive: false, versions: dependency.allowed.clone() }
+                } else {
+                    let versions = (0..self.packages[dependency.package].versions.len())
+                        .filter(|version| !dependency.allowed.contains(version))
+                        .collect();
+                    Term { package: dependency.package, positive: true, versions }
+                };
+                // An optional dependency every candidate satisfies.
+                if term.is_empty() {
+                    continue;
+                }
+                let cause = Cause::Dependency { package, version, dependency: index };
+                let id = self.push(Incompatibility::new([Term::selected(package, version), term], cause));
+                self.register(id);
+                added.push(id);
+            }
+        }
+
+        self.level += 1;
+        self.assign(Term::selected(package, version), None);
+        // Leave it to propagation to rule the version out instead.
+        if added.iter().any(|&id| matches!(self.relation(id), Relation::Satisfied)) {
+            self.assignments.pop();
+            self.level -= 1;
+            self.recompute();
+        } else {
+            self.decisions[package] = Some(version);
+        }
+        Some(package)
+    }
+
+    /// Numbered lines deriving the terminal incompatibility `id` from
+    /// dependencies.
+    fn explain(&self, id: usize) -> Vec<String> {
+        let mut lines = Vec::new();
+        if let Cause::Derived(..) = self.incompatibilities[id].cause {
+            self.explain_derived(id, &mut lines, &mut HashMap::new());
+        } else {
+            lines.push(format!("{}.", capitalize(&self.describe(id))));
+        }
+        lines
+    }
+
+    fn explain_derived(&self, id: usize, lines: &mut Vec<String>, numbers: &mut HashMap<usize, usize>) {
+        let Cause::Derived(first, second) = self.incompatibilities[id].cause else { return };
+        for cause in [first, second] {
+            if !numbers.contains_key(&cause) {
+                self.explain_derived(cause, lines, numbers);
+            }
+        }
+
+        let describe = |cause: usize| match numbers.get(&cause) {
+            Some(number) => format!("{} ({})", self.describe(cause), number),
+            None => self.describe(cause),
+        };
+        lines.push(format!(
+            "({}) Because {} and {}, {}.",
+            lines.len() + 1, describe(first), describe(second), self.describe(id)
+        ));
+        numbers.insert(id, lines.len());
+    }
+
+    fn describe(&self, id: usize) -> String {
+        let incompatibility = &self.incompatibilities[id];
+        match incompatibility.cause {
+            Cause::Root => "the mod set is required".to_string(),
+            Cause::Dependency { package, version, dependency } => {
+                let subject = self.describe_version(package, version);
+                let dependency = &self.packages[package].versions[version].dependencies[dependency];
+                let required = format!("{}{}", self.packages[dependency.package].name, describe_range(&dependency.range));
+                match (dependency.mandatory, dependency.allowed.is_empty()) {
+                    (true, true) => format!("{} requires {}, which no candidate provides", subject, required),
+                    (true, false) => format!("{} requires {}", subject, required),
+                    (false, _) => format!("{} only works with {} when both are present", subject, required),
+                }
+            }
+            Cause::Derived(..) => {
+                let (mut positive, negative): (Vec<&Term>, Vec<&Term>) = incompatibility.terms.iter()
+                    .partition(|term| term.positive);
+                positive.sort_by_key(|term| term.package != ROOT);
+                let positive: Vec<String> = positive.into_iter().map(|term| self.describe_term(term)).collect();
+                let negative: Vec<String> = negative.into_iter().map(|term| self.describe_term(term)).collect();
+                match (positive.as_slice(), negative.as_slice()) {
+                    ([], []) => "no selection is possible".to_string(),
+                    ([subject], []) if incompatibility.is_terminal() => format!("no selection satisfies {}", subject),
+                    ([subject], []) => format!("{} can't be selected", subject),
+                    ([], [required]) => format!("{} is required", required),
+                    ([subject], required) => format!("{} requires {}", subject, required.join(" or ")),
+                    ([root, others @ ..], []) if incompatibility.terms.iter().any(|t| t.package == ROOT) => {
+                        format!("{} can't include {}", root, others.join(" and "))
+                    }
+                    (subjects, []) => format!("{} are incompatible", subjects.join(" and ")),
+                    (subjects, required) => format!("{} together require {}", subjects.join(" and "), required.join(" or ")),
+                }
+            }
+        }
+    }
+
+    fn describe_version(&self, package: usize, version: usize) -> String {
+        if package == ROOT {
+            return "the mod set".to_string();
+        }
+        let package = &self.packages[package];
+        format!("{} {}", package.name, package.versions[version].label)
+    }
+
+    fn describe_term(&self, term: &Term) -> String {
+        if term.package == ROOT {
+            return "the mod set".to_string();
+        }
+        let package = &self.packages[term.package];
+        if term.versions.len() == package.versions.len() {
+            return package.name.clone();
+        }
+        let labels: Vec<&str> = term.versions.iter().map(|&v| package.versions[v].label.as_str()).collect();
+        format!("{} {}", package.name, labels.join(" or "))
+    }
+}
+
+fn describe_range(range: &str) -> String {
+    match range.trim() {
+        "" | "*" => String::new(),
+        range => format!(" {}", range),
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(name: &str, versions: Vec<(&str, Vec<Dependency>)>) -> Package {
+        Package {
+            name: name.to_string(),
+            versions: versions.into_iter()
+                .map(|(label, dependencies)| Version { label: label.to_string(), dependencies })
+                .collect(),
+        }
+    }
+
+    fn requires(package: usize, allowed: &[usize], range: &str) -> Dependency {
+        Dependency { package, allowed: allowed.iter().copied().collect(), mandatory: true, range: range.to_string() }
+    }
+
+    fn optional(package: usize, allowed: &[usize], range: &str) -> Dependency {
+        Dependency { mandatory: false, ..requires(package, allowed, range) }
+    }
+
+    #[test]
+    fn test_optional_dependencies() {
+        let packages = |required: Vec<Dependency>| vec![
+            package("the mod set", vec![("", required)]),
+            package("app", vec![("1.0.0", vec![optional(2, &[1], "<2.0.0")])]),
+            package("lib", vec![("2.0.0", vec![]), ("1.0.0", vec![])]),
+        ];
+
+        // Only constrains lib when something else requires it.
+        assert_eq!(solve(&packages(vec![requires(1, &[0], "*")])), Ok(vec![(1, 0)]));
+        assert_eq!(
+            solve(&packages(vec![requires(1, &[0], "*"), requires(2, &[0, 1], "*")])),
+            Ok(vec![(1, 0), (2, 1)]),
+        );
+    }
+
+    #[test]
+    fn test_undoes_decisions_whose_dependencies_already_fail() {
+        // lib is decided first, at 2.0.0, which app 2.0.0 doesn't accept.
+        let packages = vec![
+            package("the mod set", vec![("", vec![requires(1, &[0, 1], "*"), requires(2, &[0, 1], "*")])]),
+            package("lib", vec![("2.0.0", vec![]), ("1.0.0", vec![])]),
+            package("app", vec![
+                ("2.0.0", vec![requires(1, &[1], "<2.0.0")]),
+                ("1.0.0", vec![requires(1, &[0, 1], "*")]),
+            ]),
+        ];
+        assert_eq!(solve(&packages), Ok(vec![(1, 0), (2, 1)]));
+    }
+
+    #[test]
+    fn test_required_mod_without_candidates() {
+        let packages = vec![
+            package("the mod set", vec![("", vec![requires(1, &[], "*")])]),
+            package("missing", vec![]),
+        ];
+        assert_eq!(solve(&packages), Err(vec!["The mod set requires missing, which no candidate provides.".to_string()]));
+    }
+
+    #[test]
+    fn test_explains_in_several_steps() {
+        let packages = vec![
+            package("the mod set", vec![("", vec![requires(1, &[0, 1], "*"), requires(2, &[0], "*")])]),
+            package("app", vec![
+                ("2.0.0", vec![requires(3, &[0], ">=2.0.0")]),
+                ("1.0.0", vec![requires(4, &[0], "*")]),
+            ]),
+            package("other", vec![("1.0.0", vec![requires(3, &[1], "<2.0.0")])]),
+            package("lib", vec![("2.0.0", vec![]), ("1.0.0", vec![])]),
+            package("api", vec![("1.0.0", vec![requires(3, &[0], ">=2.0.0")])]),
+        ];
+        assert_eq!(solve(&packages).unwrap_err(), vec![
+            "(1) Because app 2.0.0 requires lib >=2.0.0 and app 1.0.0 requires api, app requires lib 2.0.0 or api.",
+            "(2) Because app requires lib 2.0.0 or api (1) and api 1.0.0 requires lib >=2.0.0, app requires lib 2.0.0.",
+            "(3) Because other 1.0.0 requires lib <2.0.0 and app requires lib 2.0.0 (2), other and app are incompatible.",
+            "(4) Because other and app are incompatible (3) and the mod set requires app, the mod set can't include other.",
+            "(5) Because the mod set can't include other (4) and the mod set requires other, no selection satisfies the mod set.",
+        ]);
+    }
+}
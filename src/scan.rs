@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use crate::error::{Error, Result};
+use crate::hash::FileHashes;
+use crate::jar::Archive;
+use crate::pack::{self, Origin};
+use crate::r#mod::{Environment, ModMetadata, ParserRegistry};
+
+/// Suffix launchers append to a jar's file name to disable it.
+pub const DISABLED_SUFFIX: &str = ".disabled";
+
+pub struct ScanOptions {
+    /// Descend into subdirectories, e.g. Forge's `mods/<mcversion>/`.
+    pub recursive: bool,
+    /// Only load jars whose path relative to the scanned directory matches one
+    /// of these patterns. Every jar matches when empty.
+    pub include: GlobSet,
+    /// Skip jars whose relative path matches one of these patterns.
+    pub exclude: GlobSet,
+    /// The parsers jars are read with, the Fabric, Forge and NeoForge ones by default.
+    pub parsers: ParserRegistry,
+}
+
+impl ScanOptions {
+    pub fn new(recursive: bool, include: &[String], exclude: &[String]) -> Result<Self> {
+        Ok(ScanOptions {
+            recursive,
+            include: build_glob_set(include)?,
+            exclude: build_glob_set(exclude)?,
+            parsers: ParserRegistry::default(),
+        })
+    }
+
+    pub fn accepts(&self, relative_path: &str) -> bool {
+        (self.include.is_empty() || self.include.is_match(relative_path))
+            && !self.exclude.is_match(relative_path)
+    }
+}
+
+/// A scanned jar.
+#[derive(Debug, Clone)]
+pub struct ModFile {
+    path: Option<PathBuf>,
+    pub size: u64,
+    hashes: OnceLock<FileHashes>,
+}
+
+impl ModFile {
+    /// Describes the jar at `path` with contents `bytes`. The hashes of jars on
+    /// disk are only computed when first needed, by reading the file again.
+    pub fn of(path: Option<PathBuf>, bytes: &[u8]) -> Self {
+        let hashes = OnceLock::new();
+        if path.is_none() {
+            let _ = hashes.set(FileHashes::of(bytes));
+        }
+        ModFile { path, size: bytes.len() as u64, hashes }
+    }
+
+    /// Location on disk of the jar. Jars read from inside a modpack have none.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn hashes(&self) -> Result<&FileHashes> {
+        if let Some(hashes) = self.hashes.get() {
+            return Ok(hashes);
+        }
+        let path = self.path.as_ref().expect("jars without a path are hashed when scanned");
+        let bytes = std::fs::read(path)
+            .map_err(|e| Error::io("read", path, e))?;
+        Ok(self.hashes.get_or_init(|| FileHashes::of(&bytes)))
+    }
+}
+
+/// The mods found in one or more directories, analyzed as a single set.
+#[derive(Default)]
+pub struct ScannedMods {
+    pub enabled: Vec<ModMetadata>,
+    /// Jars launchers disabled by appending `.disabled` to their name.
+    pub disabled: Vec<ModMetadata>,
+    /// Every jar, keyed by `ModMetadata::file_name`.
+    pub files: HashMap<String, ModFile>,
+    /// The game and loader versions declared by a scanned modpack.
+    pub environment: Environment,
+    /// Jars a scanned modpack downloads at install time rather than bundling.
+    pub unavailable: Vec<String>,
+    /// Where the jars of a scanned modpack come from, keyed by `ModMetadata::file_name`.
+    pub origins: HashMap<String, Origin>,
+    /// Inconsistencies between a scanned modpack and its jars.
+    pub warnings: Vec<String>,
+    /// Jars that couldn't be parsed, with the reason, keyed like `files`.
+    pub skipped: Vec<(String, Error)>,
+}
+
+/// Loads every jar in `dirs`, which may also be modpack files.
+///
+/// Jars are named by their path relative to the directory they were found in,
+/// using `/` as separator. Jars from every directory but the first are
+/// prefixed with that directory, so that names stay unique.
+pub fn scan_dirs(dirs: &[PathBuf], options: &ScanOptions) -> Result<ScannedMods> {
+    let mut scanned = ScannedMods::default();
+
+    for (i, dir) in dirs.iter().enumerate() {
+        let prefix = if i == 0 { String::new() } else { format!("{}/", dir.display()) };
+        if pack::is_pack(dir) {
+            let pack = pack::read_pack(dir, options).map_err(|e| Error::pack(dir, e))?;
+            scanned.enabled.extend(pack.mods.into_iter().map(|mut mod_data| {
+                mod_data.file_name = format!("{}{}", prefix, mod_data.file_name);
+                mod_data
+            }));
+            scanned.files.extend(pack.files.into_iter().map(|(file_name, file)| (format!("{}{}", prefix, file_name), file)));
+            scanned.unavailable.extend(pack.unavailable.iter().map(|path| format!("{}{}", prefix, path)));
+            scanned.origins.extend(pack.origins.into_iter().map(|(file_name, origin)| (format!("{}{}", prefix, file_name), origin)));
+            scanned.warnings.extend(pack.warnings);
+            scanned.skipped.extend(pack.skipped.into_iter().map(|(file_name, e)| (format!("{}{}", prefix, file_name), e)));
+            if let Some(mods_dir) = &pack.mods_dir {
+                scan_dir(mods_dir, mods_dir, &prefix, options, &mut scanned)?;
+            }
+            if scanned.environment == Environment::default() {
+                scanned.environment = pack.environment;
+            }
+        } else {
+            scan_dir(dir, dir, &prefix, options, &mut scanned)?;
+        }
+    }
+
+    Ok(scanned)
+}
+
+fn scan_dir(root: &Path, dir: &Path, prefix: &str, options: &ScanOptions, scanned: &mut ScannedMods) -> Result<()> {
+    let mut entries: Vec<_> = std::fs::read_dir(dir)
+        .and_then(|entries| entries.collect::<std::io::Result<_>>())
+        .map_err(|e| Error::io("read directory", dir, e))?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let path = entry.path();
+
+        if path.is_dir() {
+            if options.recursive {
+                scan_dir(root, &path, prefix, options, scanned)?;
+            }
+            continue;
+        }
+
+        let relative_path = path.strip_prefix(root).unwrap_or(&path)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let disabled = relative_path.ends_with(&format!(".jar{}", DISABLED_SUFFIX));
+        if !(relative_path.ends_with(".jar") || disabled) {
+            continue;
+        }
+        if !options.accepts(relative_path.strip_suffix(DISABLED_SUFFIX).unwrap_or(&relative_path)) {
+            continue;
+        }
+
+        let file_name = format!("{}{}", prefix, relative_path);
+        let bytes = std::fs::read(&path)
+            .map_err(|e| Error::io("read", &path, e))?;
+        match options.parsers.parse_bytes(&bytes, &file_name) {
+            Ok(mod_data_vec) => {
+                if disabled {
+                    scanned.disabled.extend(mod_data_vec);
+                } else {
+                    scanned.enabled.extend(mod_data_vec);
+                }
+                scanned.files.insert(file_name, ModFile::of(Some(path), &bytes));
+            }
+            Err(e) => scanned.skipped.push((file_name, e)),
+        }
+    }
+
+    Ok(())
+}
+
+/// Parses the jar at `path` with the default parsers.
+pub fn parse_mod_file(path: &Path) -> Result<Vec<ModMetadata>> {
+    ParserRegistry::default().parse_file(path)
+}
+
+/// Parses the metadata of a jar read in memory, e.g. from inside a modpack.
+pub fn parse_jar_bytes(bytes: &[u8], file_name: &str) -> Result<Vec<ModMetadata>> {
+    ParserRegistry::default().parse_bytes(bytes, file_name)
+}
+
+/// Parses the metadata of a jar that is already open, e.g. one read from a modpack.
+pub fn parse_mod_archive(archive: &mut dyn Archive, file_name: &str) -> Result<Vec<ModMetadata>> {
+    ParserRegistry::default().parse_archive(archive, file_name)
+}
+
+fn build_glob_set(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern).map_err(|source| Error::Glob { pattern: pattern.clone(), source })?);
+    }
+    builder.build().map_err(|source| Error::Glob { pattern: patterns.join(", "), source })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_options_accepts() {
+        let options = ScanOptions::new(
+            true,
+            &["**/*.jar".to_string()],
+            &["client/**".to_string(), "*-dev.jar".to_string()],
+        ).unwrap();
+
+        assert!(options.accepts("sodium.jar"));
+        assert!(options.accepts("1.20.1/create.jar"));
+        assert!(!options.accepts("client/zoomify.jar"));
+        assert!(!options.accepts("mymod-dev.jar"));
+    }
+}
@@ -0,0 +1,119 @@
+use std::collections::HashSet;
+use std::mem::{self, Discriminant};
+
+use anyhow::{bail, Result};
+use mmod::{DependencyError, ModMetadata};
+
+/// Returns a copy of `mods` without the jars matching `remove` (by mod id or
+/// file name) and with the mods of `add`. Nothing is changed on disk.
+pub fn modified_mod_set(mods: &[ModMetadata], remove: &[String], add: Vec<ModMetadata>) -> Result<Vec<ModMetadata>> {
+    let mut removed_files = Vec::new();
+    for target in remove {
+        let files: Vec<_> = mods.iter()
+            .filter(|m| &m.mod_id == target || &m.file_name == target)
+            .map(|m| m.file_name.clone())
+            .collect();
+        if files.is_empty() {
+            bail!("No mod or file named {} to remove", target);
+        }
+        removed_files.extend(files);
+    }
+
+    let mut modified: Vec<_> = mods.iter()
+        .filter(|m| !removed_files.contains(&m.file_name))
+        .cloned()
+        .collect();
+    modified.extend(add);
+    Ok(modified)
+}
+
+/// Identifies a diagnostic by its kind, the mod it's about and the dependency
+/// involved, so an error whose file list or version text changed still counts
+/// as the same error.
+fn error_key(error: &DependencyError) -> (Discriminant<DependencyError>, String, String) {
+    let (subject, dependency) = match error {
+        DependencyError::UnsupportedPlatform(platform, _) => (format!("{:?}", platform), String::new()),
+        DependencyError::MissingDependency(mod_id, _, dependency_id, _)
+        | DependencyError::IncompatibleEnvironment(mod_id, _, dependency_id, _, _) => {
+            (mod_id.clone(), dependency_id.clone())
+        }
+        // Only the requiring jar's file name is known here.
+        DependencyError::VersionConflict(file_name, dependency_id, ..) => (file_name.clone(), dependency_id.clone()),
+        DependencyError::CircularDependency(members) => {
+            let mut members = members.clone();
+            members.sort();
+            (members.join(","), String::new())
+        }
+        DependencyError::InvalidVersionFormat(mod_id, ..)
+        | DependencyError::UnsupportedByBridge(mod_id, ..)
+        | DependencyError::DuplicateMod(mod_id, _) => (mod_id.clone(), String::new()),
+        DependencyError::WrongLoader(platform, loader, _) => (format!("{:?}", platform), format!("{:?}", loader)),
+    };
+    (mem::discriminant(error), subject, dependency)
+}
+
+/// Splits the diagnostics into the ones only present `after` (introduced)
+/// and the ones only present `before` (fixed), matching them by kind, mod and
+/// dependency rather than by their full text.
+pub fn diff_errors<'a>(
+    before: &'a [DependencyError],
+    after: &'a [DependencyError],
+) -> (Vec<&'a DependencyError>, Vec<&'a DependencyError>) {
+    let before_keys: HashSet<_> = before.iter().map(error_key).collect();
+    let after_keys: HashSet<_> = after.iter().map(error_key).collect();
+    let introduced = after.iter().filter(|e| !before_keys.contains(&error_key(e))).collect();
+    let fixed = before.iter().filter(|e| !after_keys.contains(&error_key(e))).collect();
+    (introduced, fixed)
+}
+
+pub fn print_diff(before: &[DependencyError], after: &[DependencyError]) {
+    let (introduced, fixed) = diff_errors(before, after);
+
+    if introduced.is_empty() && fixed.is_empty() {
+        println!("No change in diagnostics ({} before and after)", before.len());
+        return;
+    }
+
+    for (title, errors) in [("New errors", introduced), ("Fixed errors", fixed)] {
+        if errors.is_empty() {
+            continue;
+        }
+        println!("{} ({}):", title, errors.len());
+        for error in errors {
+            println!("  {}", error.to_string().replace('\n', "\n  "));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_errors() {
+        let missing = |dep: &str| DependencyError::MissingDependency("app".to_string(), "app.jar".to_string(), dep.to_string(), None);
+        let before = vec![missing("a"), missing("b")];
+        let after = vec![missing("b"), missing("c")];
+
+        let (introduced, fixed) = diff_errors(&before, &after);
+        assert_eq!(introduced, vec![&missing("c")]);
+        assert_eq!(fixed, vec![&missing("a")]);
+    }
+
+    #[test]
+    fn test_diff_errors_ignores_changed_details() {
+        let duplicate = |files: &[&str]| DependencyError::DuplicateMod(
+            "lib".to_string(),
+            files.iter().map(|f| (f.to_string(), "1.0.0".to_string())).collect(),
+        );
+        let conflict = |found: &str| DependencyError::VersionConflict(
+            "app.jar".to_string(), "lib".to_string(), ">=2.0.0".to_string(), found.to_string(), format!("lib-{}.jar", found),
+        );
+        let before = vec![duplicate(&["lib-a.jar", "lib-b.jar"]), conflict("1.0.0")];
+        let after = vec![duplicate(&["lib-a.jar", "lib-b.jar", "lib-c.jar"]), conflict("1.5.0")];
+
+        let (introduced, fixed) = diff_errors(&before, &after);
+        assert!(introduced.is_empty());
+        assert!(fixed.is_empty());
+    }
+}
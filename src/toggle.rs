@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+use anyhow::{bail, Context, Result};
+use mmod::{analyze_dependencies, dependency_graph, find_orphans, DependencyError, ModFile, ModMetadata, Platform, DISABLED_SUFFIX};
+use crate::simulate::diff_errors;
+
+/// The jars to rename, and the problems renaming them would introduce.
+#[derive(Debug)]
+pub struct TogglePlan {
+    pub files: Vec<String>,
+    pub new_errors: Vec<DependencyError>,
+}
+
+impl TogglePlan {
+    /// Whether the change leaves some mod without one of its mandatory dependencies.
+    pub fn breaks_dependencies(&self) -> bool {
+        self.new_errors.iter().any(|e| matches!(e, DependencyError::MissingDependency(..)))
+    }
+}
+
+/// Plans disabling the jar that contains `mod_id`.
+///
+/// With `with_dependents`, the jars of mods that would lose a mandatory
+/// dependency are disabled as well. With `with_orphans`, so are the jars that
+/// `find_orphans` (given `libraries`) only reports once the others are gone.
+pub fn plan_disable(
+    enabled: &[ModMetadata],
+    mod_id: &str,
+    loader: Option<&Platform>,
+    with_dependents: bool,
+    with_orphans: bool,
+    libraries: &[String],
+) -> Result<TogglePlan> {
+    let mut files = files_of(enabled, mod_id)
+        .with_context(|| format!("No enabled mod with id {}", mod_id))?;
+    let before = errors_of(enabled, loader);
+    let orphans_before: Vec<_> = find_orphans(&dependency_graph(enabled, loader), libraries)
+        .into_iter()
+        .map(|m| m.file_name.clone())
+        .collect();
+
+    loop {
+        let remaining = without_files(enabled, &files);
+        let mut added = Vec::new();
+
+        if with_dependents {
+            let after = errors_of(&remaining, loader);
+            let (introduced, _) = diff_errors(&before, &after);
+            for error in introduced {
+                if let DependencyError::MissingDependency(_, file_name, ..) = error
+                    && !files.contains(file_name) && !added.contains(file_name) {
+                    added.push(file_name.clone());
+                }
+            }
+        }
+
+        if with_orphans {
+            // Libraries that only became orphans by disabling `files`
+            let graph = dependency_graph(&remaining, loader);
+            for orphan in find_orphans(&graph, libraries) {
+                if !orphans_before.contains(&orphan.file_name) && !added.contains(&orphan.file_name) {
+                    added.push(orphan.file_name.clone());
+                }
+            }
+        }
+
+        if added.is_empty() {
+            let after = errors_of(&remaining, loader);
+            let (introduced, _) = diff_errors(&before, &after);
+            let new_errors = introduced.into_iter().cloned().collect();
+            return Ok(TogglePlan { files, new_errors });
+        }
+        files.extend(added);
+    }
+}
+
+/// Plans enabling the disabled jar that contains `mod_id`.
+///
+/// With `with_dependencies`, disabled jars providing mandatory dependencies
+/// the enabled mods would miss are enabled as well.
+pub fn plan_enable(
+    enabled: &[ModMetadata],
+    disabled: &[ModMetadata],
+    mod_id: &str,
+    loader: Option<&Platform>,
+    with_dependencies: bool,
+) -> Result<TogglePlan> {
+    let mut files = files_of(disabled, mod_id)
+        .with_context(|| format!("No disabled mod with id {}", mod_id))?;
+    let before = errors_of(enabled, loader);
+
+    loop {
+        let mut candidate = enabled.to_vec();
+        candidate.extend(disabled.iter()
+            .filter(|m| files.contains(&m.file_name))
+            .map(|m| ModMetadata { file_name: enabled_file_name(&m.file_name).to_string(), ..m.clone() }));
+
+        let after = errors_of(&candidate, loader);
+        let (introduced, _) = diff_errors(&before, &after);
+
+        let mut added = Vec::new();
+        if with_dependencies {
+            for error in &introduced {
+                if let DependencyError::MissingDependency(_, _, dependency_id, _) = error {
+                    for file_name in files_of(disabled, dependency_id).unwrap_or_default() {
+                        if !files.contains(&file_name) && !added.contains(&file_name) {
+                            added.push(file_name);
+                        }
+                    }
+                }
+            }
+        }
+
+        if added.is_empty() {
+            let new_errors = introduced.into_iter().cloned().collect();
+            return Ok(TogglePlan { files, new_errors });
+        }
+        files.extend(added);
+    }
+}
+
+/// Renames the planned jars, found through `files`, appending `DISABLED_SUFFIX`
+/// when disabling and stripping it when enabling.
+pub fn apply(files: &HashMap<String, ModFile>, plan: &TogglePlan, disable: bool) -> Result<()> {
+    for file_name in &plan.files {
+        let from = files.get(file_name)
+            .and_then(|file| file.path())
+            .with_context(|| format!("{} is not a file on disk and can't be renamed", file_name))?;
+        let from_name = from.file_name().unwrap().to_string_lossy();
+        let to = if disable {
+            from.with_file_name(format!("{}{}", from_name, DISABLED_SUFFIX))
+        } else {
+            from.with_file_name(enabled_file_name(&from_name))
+        };
+        if to.exists() {
+            bail!("Cannot rename {}: {} already exists", from.display(), to.display());
+        }
+        std::fs::rename(from, &to)
+            .with_context(|| format!("Failed to rename {} to {}", from.display(), to.display()))?;
+        println!("[✓] {} -> {}", file_name, to.file_name().unwrap().to_string_lossy());
+    }
+    Ok(())
+}
+
+pub fn enabled_file_name(file_name: &str) -> &str {
+    file_name.strip_suffix(DISABLED_SUFFIX).unwrap_or(file_name)
+}
+
+/// The files that contain `mod_id`, or `None` if there are none.
+fn files_of(mods: &[ModMetadata], mod_id: &str) -> Option<Vec<String>> {
+    let mut files: Vec<String> = mods.iter()
+        .filter(|m| m.mod_id == mod_id)
+        .map(|m| m.file_name.clone())
+        .collect();
+    files.dedup();
+    (!files.is_empty()).then_some(files)
+}
+
+fn without_files(mods: &[ModMetadata], files: &[String]) -> Vec<ModMetadata> {
+    mods.iter().filter(|m| !files.contains(&m.file_name)).cloned().collect()
+}
+
+fn errors_of(mods: &[ModMetadata], loader: Option<&Platform>) -> Vec<DependencyError> {
+    analyze_dependencies(mods, loader).err().map(|e| e.0).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_disable() {
+        let enabled = [
+            ModMetadata::new("app", "1.0.0").with_file_name("app.jar").with_dependencies(&[("lib", "*")]),
+            ModMetadata::new("lib", "1.0.0").with_file_name("lib.jar").with_dependencies(&[("core", "*")]),
+            ModMetadata::new("core", "1.0.0").with_file_name("core.jar"),
+        ];
+
+        let plan = plan_disable(&enabled, "lib", None, false, false, &[]).unwrap();
+        assert_eq!(plan.files, vec!["lib.jar"]);
+        assert!(plan.breaks_dependencies());
+
+        let plan = plan_disable(&enabled, "lib", None, true, true, &["core".to_string()]).unwrap();
+        assert_eq!(plan.files, vec!["lib.jar", "app.jar", "core.jar"]);
+        assert!(!plan.breaks_dependencies());
+    }
+
+    #[test]
+    fn test_plan_disable_with_orphans_only_takes_libraries() {
+        let enabled = [
+            ModMetadata::new("app", "1.0.0")
+                .with_file_name("app.jar")
+                .with_dependencies(&[("lib", "*"), ("shared", "*")])
+                .with_optional_dependencies(&[("map", "*")]),
+            ModMetadata::new("lib", "1.0.0").with_file_name("lib.jar").with_library(true),
+            ModMetadata::new("map", "1.0.0").with_file_name("map.jar"),
+            ModMetadata::new("shared", "1.0.0").with_file_name("bundle.jar").with_library(true),
+            ModMetadata::new("other", "1.0.0").with_file_name("bundle.jar"),
+            ModMetadata::new("old_lib", "1.0.0").with_file_name("old_lib.jar").with_library(true),
+            ModMetadata::new("tool", "1.0.0").with_file_name("tool.jar").with_dependencies(&[("other", "*")]),
+        ];
+
+        // `map` is optional content, `bundle.jar` still holds `other` for `tool`,
+        // and `old_lib` was already an orphan before.
+        let plan = plan_disable(&enabled, "app", None, false, true, &[]).unwrap();
+        assert_eq!(plan.files, vec!["app.jar", "lib.jar"]);
+    }
+
+    #[test]
+    fn test_plan_enable() {
+        let enabled = [ModMetadata::new("core", "1.0.0").with_file_name("core.jar")];
+        let disabled = [
+            ModMetadata::new("app", "1.0.0").with_file_name("app.jar.disabled").with_dependencies(&[("lib", "*")]),
+            ModMetadata::new("lib", "1.0.0").with_file_name("lib.jar.disabled").with_dependencies(&[("core", "*")]),
+        ];
+
+        let plan = plan_enable(&enabled, &disabled, "app", None, false).unwrap();
+        assert!(plan.breaks_dependencies());
+
+        let plan = plan_enable(&enabled, &disabled, "app", None, true).unwrap();
+        assert_eq!(plan.files, vec!["app.jar.disabled", "lib.jar.disabled"]);
+        assert!(!plan.breaks_dependencies());
+    }
+}
@@ -0,0 +1,234 @@
+use std::collections::HashSet;
+use anyhow::{Context, Result};
+use mmod::{is_platform_dependency, DependencyGraph, ModDependency, ModMetadata};
+
+pub struct TreeOptions {
+    /// How many levels below the root to print, or everything when `None`.
+    pub depth: Option<usize>,
+    /// Print the mods that depend on each mod instead of its dependencies.
+    pub invert: bool,
+}
+
+/// Prints the dependency tree of `mod_id`, or of every top-level mod.
+///
+/// A mod whose subtree has already been printed is marked `(*)` and not expanded again.
+pub fn print_tree(graph: &DependencyGraph, mod_id: Option<&str>, options: &TreeOptions) -> Result<()> {
+    print!("{}", render_tree(graph, mod_id, options)?);
+    Ok(())
+}
+
+fn render_tree(graph: &DependencyGraph, mod_id: Option<&str>, options: &TreeOptions) -> Result<String> {
+    let roots = match mod_id {
+        Some(mod_id) => vec![graph.get(mod_id).with_context(|| format!("Mod not found: {}", mod_id))?],
+        None => {
+            let mut roots: Vec<_> = graph.nodes()
+                .iter()
+                .copied()
+                .filter(|m| {
+                    if options.invert {
+                        graph.edges(m).next().is_none()
+                    } else {
+                        graph.dependents(m).is_empty()
+                    }
+                })
+                .collect();
+            roots.sort_by(|a, b| a.mod_id.cmp(&b.mod_id));
+            roots
+        }
+    };
+
+    let mut printer = TreePrinter { graph, options, expanded: HashSet::new(), out: String::new() };
+    let mut reached = HashSet::new();
+    for root in roots {
+        printer.print_root(root);
+        printer.reach(root, &mut reached);
+    }
+
+    // Mods that only depend on each other have no top-level mod above them.
+    if mod_id.is_none() {
+        let mut unreached: Vec<_> = graph.nodes()
+            .iter()
+            .copied()
+            .filter(|m| !reached.contains(m.mod_id.as_str()))
+            .collect();
+        unreached.sort_by(|a, b| a.mod_id.cmp(&b.mod_id));
+        for root in unreached {
+            if !reached.contains(root.mod_id.as_str()) {
+                printer.print_root(root);
+                printer.reach(root, &mut reached);
+            }
+        }
+    }
+
+    Ok(printer.out)
+}
+
+struct TreePrinter<'g, 'a> {
+    graph: &'g DependencyGraph<'a>,
+    options: &'g TreeOptions,
+    expanded: HashSet<&'a str>,
+    out: String,
+}
+
+enum Child<'a> {
+    Present(&'a ModMetadata, &'a ModDependency, bool),
+    Missing(&'a ModDependency),
+}
+
+impl<'a> TreePrinter<'_, 'a> {
+    fn print_root(&mut self, root: &'a ModMetadata) {
+        self.out.push_str(&format!("{}\n", node_label(root)));
+        self.print_children(root, "", 1);
+    }
+
+    /// Adds `node` and every mod below it to `reached`, whatever the depth.
+    fn reach(&self, node: &'a ModMetadata, reached: &mut HashSet<&'a str>) {
+        if !reached.insert(node.mod_id.as_str()) {
+            return;
+        }
+        for child in self.children(node) {
+            if let Child::Present(mod_, _, _) = child {
+                self.reach(mod_, reached);
+            }
+        }
+    }
+
+    fn print_children(&mut self, node: &'a ModMetadata, prefix: &str, depth: usize) {
+        if self.options.depth.is_some_and(|max| depth > max) {
+            return;
+        }
+        self.expanded.insert(node.mod_id.as_str());
+
+        let children = self.children(node);
+        for (i, child) in children.iter().enumerate() {
+            let last = i + 1 == children.len();
+            let branch = if last { "└── " } else { "├── " };
+            let child_prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+
+            match child {
+                Child::Present(mod_, dependency, satisfied) => {
+                    let repeated = self.expanded.contains(mod_.mod_id.as_str())
+                        && !self.children(mod_).is_empty();
+                    self.out.push_str(&format!(
+                        "{}{}{} {}{}\n",
+                        prefix,
+                        branch,
+                        node_label(mod_),
+                        edge_label(dependency, *satisfied),
+                        if repeated { " (*)" } else { "" },
+                    ));
+                    if !repeated {
+                        self.print_children(mod_, &child_prefix, depth + 1);
+                    }
+                }
+                Child::Missing(dependency) => {
+                    self.out.push_str(&format!(
+                        "{}{}{} (missing) {}\n",
+                        prefix, branch, dependency.mod_id, edge_label(dependency, false),
+                    ));
+                }
+            }
+        }
+    }
+
+    fn children(&self, node: &'a ModMetadata) -> Vec<Child<'a>> {
+        let mut children: Vec<_> = if self.options.invert {
+            self.graph.dependents(node)
+                .into_iter()
+                .map(|edge| Child::Present(edge.from, edge.dependency, edge.dependency.version_range.matches(&node.version)))
+                .collect()
+        } else {
+            node.dependencies
+                .iter()
+                .filter(|dep| !is_platform_dependency(&dep.mod_id))
+                .filter_map(|dep| match self.graph.get(&dep.mod_id) {
+                    Some(mod_) => Some(Child::Present(mod_, dep, dep.version_range.matches(&mod_.version))),
+                    None if dep.mandatory => Some(Child::Missing(dep)),
+                    None => None,
+                })
+                .collect()
+        };
+
+        children.sort_by(|a, b| child_id(a).cmp(child_id(b)));
+        children
+    }
+}
+
+fn child_id<'a>(child: &Child<'a>) -> &'a str {
+    match child {
+        Child::Present(mod_, _, _) => &mod_.mod_id,
+        Child::Missing(dependency) => &dependency.mod_id,
+    }
+}
+
+fn node_label(mod_: &ModMetadata) -> String {
+    format!("{} v{} ({})", mod_.mod_id, mod_.version, mod_.file_name)
+}
+
+fn edge_label(dependency: &ModDependency, satisfied: bool) -> String {
+    format!(
+        "[{} {} {}]",
+        if dependency.mandatory { "mandatory" } else { "optional" },
+        dependency.version_range,
+        if satisfied { "✓" } else { "✗" },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mods() -> Vec<ModMetadata> {
+        vec![
+            ModMetadata::new("app", "1.0.0").with_dependencies(&[("ui", "*"), ("lib", ">=2.0"), ("gone", "*")]),
+            ModMetadata::new("ui", "1.0.0").with_dependencies(&[("lib", "*")]),
+            ModMetadata::new("lib", "1.0.0").with_dependencies(&[("core", "*")]),
+            ModMetadata::new("core", "1.0.0"),
+        ]
+    }
+
+    fn render(mods: &[ModMetadata], mod_id: Option<&str>, depth: Option<usize>, invert: bool) -> String {
+        let refs: Vec<_> = mods.iter().collect();
+        render_tree(&DependencyGraph::new(&refs), mod_id, &TreeOptions { depth, invert }).unwrap()
+    }
+
+    #[test]
+    fn test_repeated_subtrees_are_marked() {
+        assert_eq!(render(&mods(), None, None, false), "\
+app v1.0.0 (app-1.0.0.jar)
+├── gone (missing) [mandatory * ✗]
+├── lib v1.0.0 (lib-1.0.0.jar) [mandatory >=2.0 ✗]
+│   └── core v1.0.0 (core-1.0.0.jar) [mandatory * ✓]
+└── ui v1.0.0 (ui-1.0.0.jar) [mandatory * ✓]
+    └── lib v1.0.0 (lib-1.0.0.jar) [mandatory * ✓] (*)
+");
+    }
+
+    #[test]
+    fn test_depth() {
+        assert_eq!(render(&mods(), Some("ui"), Some(1), false), "\
+ui v1.0.0 (ui-1.0.0.jar)
+└── lib v1.0.0 (lib-1.0.0.jar) [mandatory * ✓]
+");
+    }
+
+    #[test]
+    fn test_depth_without_root() {
+        assert_eq!(render(&mods(), None, Some(1), false), "\
+app v1.0.0 (app-1.0.0.jar)
+├── gone (missing) [mandatory * ✗]
+├── lib v1.0.0 (lib-1.0.0.jar) [mandatory >=2.0 ✗]
+└── ui v1.0.0 (ui-1.0.0.jar) [mandatory * ✓]
+");
+    }
+
+    #[test]
+    fn test_invert() {
+        assert_eq!(render(&mods(), Some("lib"), None, true), "\
+lib v1.0.0 (lib-1.0.0.jar)
+├── app v1.0.0 (app-1.0.0.jar) [mandatory >=2.0 ✗]
+└── ui v1.0.0 (ui-1.0.0.jar) [mandatory * ✓]
+    └── app v1.0.0 (app-1.0.0.jar) [mandatory * ✓]
+");
+    }
+}
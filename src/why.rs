@@ -0,0 +1,77 @@
+use anyhow::{Context, Result};
+use mmod::DependencyGraph;
+
+/// Prints every dependency chain from a top-level mod down to `mod_id`.
+pub fn print_why(graph: &DependencyGraph, mod_id: &str) -> Result<()> {
+    print!("{}", render_why(graph, mod_id)?);
+    Ok(())
+}
+
+fn render_why(graph: &DependencyGraph, mod_id: &str) -> Result<String> {
+    let target = graph.get(mod_id)
+        .with_context(|| format!("Mod not found: {}", mod_id))?;
+
+    let mut out = format!("{} {} ({})\n", target.mod_id, target.version, target.file_name);
+
+    let paths = graph.paths_to(target);
+    if paths.is_empty() {
+        out.push_str("  top-level: no mod depends on it\n");
+        return Ok(out);
+    }
+
+    for path in paths {
+        // A chain also stops when every dependent of its first mod is already on it.
+        let head = path[0].from;
+        let mut line = if graph.dependents(head).is_empty() {
+            format!("{} (top-level)", head.mod_id)
+        } else {
+            format!("{} (cycle)", head.mod_id)
+        };
+        for edge in path {
+            line.push_str(&format!(
+                " -> {} [{}, {}]",
+                edge.to.mod_id,
+                if edge.dependency.mandatory { "mandatory" } else { "optional" },
+                edge.dependency.version_range,
+            ));
+        }
+        out.push_str(&format!("  {}\n", line));
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mmod::ModMetadata;
+
+    #[test]
+    fn test_render_why() {
+        let mods = [
+            ModMetadata::new("app", "1.0.0").with_dependency("lib", "*", true),
+            ModMetadata::new("lib", "1.0.0").with_dependency("core", ">=1.0", false),
+            ModMetadata::new("core", "1.0.0"),
+        ];
+        let refs: Vec<_> = mods.iter().collect();
+        let out = render_why(&DependencyGraph::new(&refs), "core").unwrap();
+        assert_eq!(out, "core 1.0.0 (core-1.0.0.jar)\n  app (top-level) -> lib [mandatory, *] -> core [optional, >=1.0]\n");
+
+        let out = render_why(&DependencyGraph::new(&refs), "app").unwrap();
+        assert!(out.ends_with("  top-level: no mod depends on it\n"), "{}", out);
+        assert!(render_why(&DependencyGraph::new(&refs), "missing").is_err());
+    }
+
+    #[test]
+    fn test_chain_into_a_cycle_is_not_top_level() {
+        let mods = [
+            ModMetadata::new("a", "1.0.0").with_dependency("b", "*", true),
+            ModMetadata::new("b", "1.0.0").with_dependencies(&[("a", "*"), ("lib", "*")]),
+            ModMetadata::new("lib", "1.0.0"),
+        ];
+        let refs: Vec<_> = mods.iter().collect();
+        let out = render_why(&DependencyGraph::new(&refs), "lib").unwrap();
+        assert!(out.contains("  a (cycle) -> b [mandatory, *] -> lib [mandatory, *]\n"), "{}", out);
+        assert!(!out.contains("top-level"), "{}", out);
+    }
+}
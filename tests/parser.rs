@@ -0,0 +1,112 @@
+#[path = "../src/jar/test_util.rs"]
+mod test_util;
+
+use mmod::jar::{self, Archive};
+use mmod::{Error, ModMetadata, ModParser, ParserRegistry, Platform, Result, Side};
+use test_util::zip;
+
+/// Reads the `plugin.txt` of a made-up server plugin format, `id=version`.
+struct PluginParser;
+
+impl ModParser for PluginParser {
+    fn name(&self) -> &str {
+        "plugin.txt"
+    }
+
+    fn detect(&self, archive: &mut dyn Archive) -> bool {
+        archive.contains("plugin.txt")
+    }
+
+    fn parse(&self, archive: &mut dyn Archive, file_name: &str) -> Result<Vec<ModMetadata>> {
+        let contents = jar::read_entry(archive, "plugin.txt", file_name)?;
+        let (mod_id, version) = contents.trim().split_once('=')
+            .ok_or_else(|| Error::metadata(file_name, "plugin.txt", "expected id=version"))?;
+        Ok(vec![
+            ModMetadata::new(mod_id, version)
+                .with_file_name(file_name)
+                .with_platform(Platform::Unknown("plugin".to_string()))
+                .with_side(Side::Server),
+        ])
+    }
+}
+
+#[test]
+fn test_every_detecting_parser_reads_the_jar() {
+    let bytes = zip(&[
+        ("fabric.mod.json", br#"{"schemaVersion": 1, "id": "both", "version": "1.0.0"}"#),
+        ("plugin.txt", b"both=1.0.0"),
+    ]);
+
+    let mods = ParserRegistry::default().parse_bytes(&bytes, "both.jar").unwrap();
+    assert_eq!(mods.len(), 1);
+    assert_eq!(mods[0].platform, Platform::Fabric);
+
+    let mut registry = ParserRegistry::default();
+    registry.register(PluginParser);
+    let mods = registry.parse_bytes(&bytes, "both.jar").unwrap();
+    let platforms: Vec<_> = mods.iter().map(|mod_| &mod_.platform).collect();
+    assert_eq!(platforms, vec![&Platform::Fabric, &Platform::Unknown("plugin".to_string())]);
+}
+
+#[test]
+fn test_forge_and_neoforge_metadata_side_by_side() {
+    let bytes = zip(&[
+        ("META-INF/mods.toml", br#"
+modLoader = "javafml"
+loaderVersion = "[47,)"
+license = "MIT"
+
+[[mods]]
+modId = "shared"
+version = "2.0.0"
+
+[[dependencies.shared]]
+modId = "lib"
+mandatory = true
+versionRange = "[1.0,)"
+ordering = "NONE"
+side = "BOTH"
+"#),
+        ("META-INF/neoforge.mods.toml", br#"
+modLoader = "javafml"
+loaderVersion = "[1,)"
+license = "MIT"
+
+[[mods]]
+modId = "shared"
+version = "2.0.0"
+
+[[dependencies.shared]]
+modId = "lib"
+type = "optional"
+versionRange = ""
+ordering = "NONE"
+side = "BOTH"
+"#),
+    ]);
+
+    let mods = ParserRegistry::default().parse_bytes(&bytes, "shared.jar").unwrap();
+    let platforms: Vec<_> = mods.iter().map(|mod_| &mod_.platform).collect();
+    assert_eq!(platforms, vec![&Platform::Forge, &Platform::NeoForge]);
+    assert!(mods.iter().all(|mod_| mod_.mod_id == "shared" && mod_.file_name == "shared.jar"));
+
+    let lib = |mod_: &ModMetadata| mod_.dependencies.iter().find(|dep| dep.mod_id == "lib").map(|dep| dep.mandatory);
+    assert_eq!(lib(&mods[0]), Some(true));
+    assert_eq!(lib(&mods[1]), Some(false));
+}
+
+#[test]
+fn test_unsupported_format_lists_registered_parsers() {
+    let bytes = zip(&[("plugin.txt", b"plugin=2.0")]);
+    let error = ParserRegistry::default().parse_bytes(&bytes, "plugin.jar").unwrap_err();
+    assert!(matches!(error, Error::UnsupportedFormat { .. }));
+    assert!(error.to_string().contains("fabric.mod.json, META-INF/mods.toml (Forge)"), "{}", error);
+
+    let mut registry = ParserRegistry::empty();
+    registry.register(PluginParser);
+    let mods = registry.parse_bytes(&bytes, "plugin.jar").unwrap();
+    assert_eq!((mods[0].mod_id.as_str(), mods[0].version.as_str()), ("plugin", "2.0"));
+
+    let error = registry.parse_bytes(&zip(&[("plugin.txt", b"plugin")]), "plugin.jar").unwrap_err();
+    assert!(matches!(error, Error::Metadata { .. }), "{}", error);
+}